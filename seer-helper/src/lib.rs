@@ -1,6 +1,55 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// The environment variable that replays values seer found, e.g. in the regression tests seer
+/// generates. It holds the bytes of each `mksym` variable as hex, separated by commas, in the
+/// order the `mksym` calls happen.
+pub const REPLAY_VAR: &str = "SEER_HELPER_REPLAY";
+
+/// How many `mksym` calls have been replayed so far.
+static REPLAYED: AtomicUsize = AtomicUsize::new(0);
+
 /// Instructs Seer to make the contents of `var` symbolic.
-#[allow(unused_variables)]
+///
+/// Outside of Seer this does nothing, unless `SEER_HELPER_REPLAY` is set. Then `var` is
+/// overwritten with the next value from that variable.
 pub fn mksym<T>(var: &mut T) {
+    let replay = match std::env::var(REPLAY_VAR) {
+        Ok(replay) => replay,
+        Err(_) => return,
+    };
+    let idx = REPLAYED.fetch_add(1, Ordering::SeqCst);
+    let hex = match replay.split(',').nth(idx) {
+        Some(hex) => hex,
+        None => panic!("{} has no value for mksym call number {}", REPLAY_VAR, idx + 1),
+    };
+    let bytes = decode_hex(hex);
+    let size = std::mem::size_of::<T>();
+    if bytes.len() != size {
+        panic!("{} has {} bytes for mksym call number {}, but the variable has {}",
+               REPLAY_VAR, bytes.len(), idx + 1, size);
+    }
+    unsafe {
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), var as *mut T as *mut u8, size);
+    }
+}
+
+/// Instructs Seer to give the environment variable `name` a symbolic value, or none at all.
+/// Reading it through `std::env::var` or `getenv` then explores both cases.
+///
+/// Outside of Seer this does nothing, so the variable keeps its real value.
+pub fn mksym_env(name: &str) {
+    let _ = name;
+}
+
+fn decode_hex(hex: &str) -> Vec<u8> {
+    let hex = hex.trim();
+    if hex.len() % 2 != 0 {
+        panic!("{} contains hex of odd length: {:?}", REPLAY_VAR, hex);
+    }
+    (0..hex.len() / 2).map(|idx| {
+        u8::from_str_radix(&hex[2 * idx..2 * idx + 2], 16)
+            .unwrap_or_else(|_| panic!("{} contains invalid hex: {:?}", REPLAY_VAR, hex))
+    }).collect()
 }
 
 /// This macro must be invoked in the user crate root in order to get formatting to work. It
@@ -16,6 +16,10 @@ const CARGO_SEER_HELP: &str = r#"Attempts to run all possible execution paths th
 
 Usage:
     cargo seer [options] [--] [<opts>...]
+    cargo seer test [options] [--] [<opts>...]
+
+`cargo seer test` explores every #[test] function of the library, binaries
+and integration tests instead of main, and prints a summary for each.
 
 Common options:
     -h, --help               Print this message
@@ -86,17 +90,23 @@ pub fn main() {
             })
             .expect("could not find matching package");
         let package = metadata.packages.remove(package_index);
+        let run_tests = std::env::args().nth(2).map_or(false, |arg| arg == "test");
+        let (skip, seer_args, kinds): (usize, &[&str], &[&str]) = if run_tests {
+            (3, &["--run-tests"], &["bin", "test"])
+        } else {
+            (2, &[], &["bin", "example", "test", "bench"])
+        };
         for target in package.targets {
-            let args = std::env::args().skip(2);
+            let args = std::env::args().skip(skip);
             if let Some(first) = target.kind.get(0) {
                 if target.kind.len() > 1 || first.ends_with("lib") {
-                    if let Err(code) = process(std::iter::once("--lib".to_owned()).chain(args)) {
+                    if let Err(code) = process(std::iter::once("--lib".to_owned()).chain(args), seer_args) {
                         std::process::exit(code);
                     }
-                } else if ["bin", "example", "test", "bench"].contains(&&**first) {
+                } else if kinds.contains(&&**first) {
                     if let Err(code) = process(vec![format!("--{}", first), target.name]
                                                    .into_iter()
-                                                   .chain(args)) {
+                                                   .chain(args), seer_args) {
                         std::process::exit(code);
                     }
                 }
@@ -166,7 +176,7 @@ pub fn main() {
     }
 }
 
-fn process<I>(old_args: I) -> Result<(), i32>
+fn process<I>(old_args: I, seer_args: &[&str]) -> Result<(), i32>
     where I: Iterator<Item = String>
 {
 
@@ -181,6 +191,7 @@ fn process<I>(old_args: I) -> Result<(), i32>
         args.push("--".to_owned());
     }
     args.push("--emit=dep-info,metadata".to_owned());
+    args.extend(seer_args.iter().map(|arg| arg.to_string()));
 
     let path = std::env::current_exe().expect("current executable path invalid");
     let exit_status = std::process::Command::new("cargo")
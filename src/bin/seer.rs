@@ -8,7 +8,83 @@ const SEER_HELP: &str = r#"Attempts to find all possible execution paths in a pr
 Usage:
     seer <opts>
 
-The options are passed to rustc.
+Options:
+    --emit-error            Report the first error as a rustc diagnostic.
+    --dedup-errors          Report each kind of error at each place only
+                            once, and print how many paths ran into it.
+    --max-errors <n>        Stop after n errors (default 1, or no limit
+                            with --emit-error). Paths cut short by engine
+                            limitations do not count.
+    --max-unique-errors <n> Stop after n distinct errors. Implies that
+                            errors are deduplicated.
+    --max-paths <n>         Stop after n paths have finished.
+    --explore-all           Do not stop because of errors.
+    --time-limit <secs>     Stop exploring after this many seconds.
+    --solver-time-limit <secs>
+                            Stop exploring once the solver has spent this
+                            many seconds checking constraints.
+    --max-pending-states <n>
+                            Drop new paths while n are waiting to be
+                            explored.
+    --memory-size <bytes>   Memory every path may allocate. This and the
+                            following limits override the miri crate
+                            attributes of the same name.
+    --step-limit <n>        Steps every path may take.
+    --stack-limit <n>       Stack frames every path may have.
+    --symbolic-alloc-limit <n>
+                            Allocations of symbolic size every path may
+                            make.
+    --search <strategy>     Order in which pending paths are explored:
+                            dfs (default), bfs, random, shortest or
+                            coverage.
+    --seed <n>              Seed for the random search strategy.
+    --coverage              Print the basic blocks no path reached.
+    --merge-states          Merge paths that meet at the same basic block.
+    --entry <path>          Explore the function at this path instead of
+                            main, with symbolic values for its arguments.
+    --entry-slice-len <n>   Number of elements of slice arguments of the
+                            entry function (default 8).
+    --symbolic-args <n>     Pass n symbolic arguments to the program, after
+                            its name (default 0).
+    --symbolic-arg-len <n>  Maximum length in bytes of each symbolic
+                            argument (default 8).
+    --symbolic-env <name>   Give the environment variable a symbolic value,
+                            or none. May be given more than once.
+    --symbolic-env-len <n>  Maximum length in bytes of the value of each
+                            symbolic environment variable (default 8).
+    --file <path>           Let the program read the file at path, with its
+                            current contents. Without this or
+                            --symbolic-file, the program finds no files.
+    --symbolic-file <path>  Let the program read a file at path with
+                            symbolic contents.
+    --symbolic-file-len <n> Maximum length in bytes of each symbolic file
+                            (default 16).
+    --stdin-len <n>         Maximum length in bytes of stdin for reads that
+                            stop at its end, like read_line (default 8).
+    --short-reads           Let reads from stdin return fewer bytes than
+                            asked for, or fail.
+    --run-tests             Explore every #[test] function instead of main
+                            and print a summary for each. Every test is
+                            explored in full, so this cannot be combined
+                            with --max-errors, --max-unique-errors or
+                            --explore-all.
+    --json                  Print every finished path as a line of JSON
+                            with its inputs, error, span and call stack.
+    --corpus <dir>          Write the stdin of every finished path to a
+                            file in dir, named by the SHA-1 of its contents
+                            as in libFuzzer and cargo-fuzz corpora.
+    --corpus-errors-only    Only write the stdin of paths that ended in an
+                            error to the corpus.
+    --regression-tests <file>
+                            Write a #[test] that replays every panic found
+                            to file. Not available with --entry,
+                            --run-tests, --file, --symbolic-file or
+                            --short-reads.
+    --regression-binary <name>
+                            The binary the regression tests run (default:
+                            the name of the input file).
+
+All other options are passed to rustc.
 "#;
 
 
@@ -70,17 +146,25 @@ fn main() {
     }
 
     init_logger();
-    let consumer = |complete: ::seer::ExecutionComplete | {
-        println!("{:?}", complete);
-        if let Err(_) = complete.result {
-            println!("hit an error. halting");
-            false
-        } else {
+    let mut config = ::seer::ExecutionConfig::new();
+
+    // In JSON mode, stdout carries nothing but the JSON lines.
+    let json = match args.iter().position(|a| a == "--json") {
+        Some(idx) => {
+            args.remove(idx);
+            config.json_output(::std::io::stdout());
             true
         }
+        None => false,
     };
 
-    let mut config = ::seer::ExecutionConfig::new();
+    let run_tests = match args.iter().position(|a| a == "--run-tests") {
+        Some(idx) => {
+            args.remove(idx);
+            true
+        }
+        None => false,
+    };
 
     let mut emit_error_idx = None;
     for (idx, arg) in args.iter().enumerate() {
@@ -89,12 +173,288 @@ fn main() {
             break;
         }
     }
+    let emit_error = emit_error_idx.is_some();
     if let Some(idx) = emit_error_idx {
         config.emit_error(true);
         args.remove(idx);
+    }
+
+    // When to stop exploring. Only the first error stops it by default, except that every
+    // error is reported as a diagnostic with `--emit-error`.
+    let max_errors = take_flag_value(&mut args, "--max-errors")
+        .map(|n| parse_count("--max-errors", &n));
+    let max_unique_errors = take_flag_value(&mut args, "--max-unique-errors")
+        .map(|n| parse_count("--max-unique-errors", &n));
+    if let Some(n) = take_flag_value(&mut args, "--max-paths") {
+        config.max_paths(parse_count("--max-paths", &n));
+    }
+    let explore_all = match args.iter().position(|a| a == "--explore-all") {
+        Some(idx) => {
+            args.remove(idx);
+            true
+        }
+        None => false,
+    };
+    if run_tests && (max_errors.is_some() || max_unique_errors.is_some() || explore_all) {
+        fail("--run-tests cannot be combined with --max-errors, --max-unique-errors or \
+              --explore-all");
+    }
+    let max_errors = if explore_all {
+        None
+    } else if let Some(max) = max_unique_errors {
+        // Duplicates of an error never reach the consumer, so it sees unique errors only.
+        config.deduplicate_errors(true);
+        Some(max)
+    } else if emit_error {
+        max_errors
     } else {
+        Some(max_errors.unwrap_or(1))
+    };
+
+    if let Some(secs) = take_flag_value(&mut args, "--time-limit") {
+        let secs = parse_count("--time-limit", &secs) as u64;
+        config.time_limit(::std::time::Duration::from_secs(secs));
+    }
+    if let Some(secs) = take_flag_value(&mut args, "--solver-time-limit") {
+        let secs = parse_count("--solver-time-limit", &secs) as u64;
+        config.solver_time_limit(::std::time::Duration::from_secs(secs));
+    }
+    if let Some(n) = take_flag_value(&mut args, "--max-pending-states") {
+        config.max_pending_states(parse_count("--max-pending-states", &n));
+    }
+    if let Some(bytes) = take_flag_value(&mut args, "--memory-size") {
+        config.memory_size(parse_count("--memory-size", &bytes) as u64);
+    }
+    if let Some(n) = take_flag_value(&mut args, "--step-limit") {
+        config.step_limit(parse_count("--step-limit", &n) as u64);
+    }
+    if let Some(n) = take_flag_value(&mut args, "--stack-limit") {
+        config.stack_limit(parse_count("--stack-limit", &n));
+    }
+    if let Some(n) = take_flag_value(&mut args, "--symbolic-alloc-limit") {
+        config.symbolic_alloc_limit(parse_count("--symbolic-alloc-limit", &n) as u64);
+    }
+
+    // Diagnostics and JSON replace the `Debug` output.
+    let quiet = json || emit_error;
+    let mut errors = 0;
+    let consumer = move |complete: ::seer::ExecutionComplete | {
+        if !quiet {
+            println!("{:?}", complete);
+        }
+        match complete.result {
+            Err(ref e) if e.is_engine_limitation() => {
+                if !quiet {
+                    println!("hit an engine limitation. continuing with other paths");
+                }
+                true
+            }
+            Err(_) => {
+                errors += 1;
+                let go_on = max_errors.map_or(true, |max| errors < max);
+                if !quiet {
+                    if go_on {
+                        println!("hit an error. continuing with other paths");
+                    } else {
+                        println!("hit an error. halting");
+                    }
+                }
+                go_on
+            }
+            Ok(()) => true,
+        }
+    };
+    if !run_tests {
         config.consumer(consumer);
     }
 
+    if run_tests {
+        if !args.iter().any(|a| a == "--test") {
+            args.push("--test".to_string());
+        }
+        config.tests(true);
+        config.test_consumer(print_test_summary);
+    }
+
+    let search = take_flag_value(&mut args, "--search");
+    let seed = take_flag_value(&mut args, "--seed").map(|s| {
+        s.parse::<u64>().unwrap_or_else(|_| fail(&format!("invalid seed: {}", s)))
+    });
+    let strategy = match search.as_ref().map(|s| &s[..]) {
+        None | Some("dfs") => ::seer::SearchStrategyKind::DepthFirst,
+        Some("bfs") => ::seer::SearchStrategyKind::BreadthFirst,
+        Some("random") => ::seer::SearchStrategyKind::RandomPath { seed: seed.unwrap_or(0) },
+        Some("shortest") => ::seer::SearchStrategyKind::ShortestPathFirst,
+        Some("coverage") => ::seer::SearchStrategyKind::CoverageGuided,
+        Some(other) => fail(&format!("unknown search strategy: {}", other)),
+    };
+    config.search_strategy(strategy);
+
+    if let Some(idx) = args.iter().position(|a| a == "--merge-states") {
+        args.remove(idx);
+        config.merge_states(true);
+    }
+
+    // Regression tests replay paths through `main` with stdin, arguments and the environment.
+    let mut replayable = !run_tests;
+    if let Some(path) = take_flag_value(&mut args, "--entry") {
+        replayable = false;
+        config.entry(&path);
+    }
+    if let Some(len) = take_flag_value(&mut args, "--entry-slice-len") {
+        let len = len.parse::<u64>().unwrap_or_else(|_| fail(&format!("invalid slice length: {}", len)));
+        config.entry_slice_len(len);
+    }
+    if let Some(n) = take_flag_value(&mut args, "--symbolic-args") {
+        let n = n.parse::<usize>().unwrap_or_else(|_| fail(&format!("invalid number of arguments: {}", n)));
+        config.symbolic_args(n);
+    }
+    if let Some(len) = take_flag_value(&mut args, "--symbolic-arg-len") {
+        let len = len.parse::<u64>().unwrap_or_else(|_| fail(&format!("invalid argument length: {}", len)));
+        config.symbolic_arg_len(len);
+    }
+    while let Some(name) = take_flag_value(&mut args, "--symbolic-env") {
+        config.symbolic_env_var(&name);
+    }
+    if let Some(len) = take_flag_value(&mut args, "--symbolic-env-len") {
+        let len = len.parse::<u64>().unwrap_or_else(|_| fail(&format!("invalid value length: {}", len)));
+        config.symbolic_env_var_len(len);
+    }
+    while let Some(path) = take_flag_value(&mut args, "--file") {
+        let contents = ::std::fs::read(&path)
+            .unwrap_or_else(|e| fail(&format!("could not read {}: {}", path, e)));
+        replayable = false;
+        config.file(&path, contents);
+    }
+    let symbolic_file_len = take_flag_value(&mut args, "--symbolic-file-len").map_or(16, |len| {
+        len.parse::<u64>().unwrap_or_else(|_| fail(&format!("invalid file length: {}", len)))
+    });
+    while let Some(path) = take_flag_value(&mut args, "--symbolic-file") {
+        replayable = false;
+        config.symbolic_file(&path, symbolic_file_len);
+    }
+    if let Some(len) = take_flag_value(&mut args, "--stdin-len") {
+        let len = len.parse::<u64>().unwrap_or_else(|_| fail(&format!("invalid stdin length: {}", len)));
+        config.stdin_len(len);
+    }
+    if let Some(idx) = args.iter().position(|a| a == "--short-reads") {
+        args.remove(idx);
+        replayable = false;
+        config.short_reads(true);
+    }
+
+    if let Some(dir) = take_flag_value(&mut args, "--corpus") {
+        config.corpus_dir(dir);
+    }
+    if let Some(idx) = args.iter().position(|a| a == "--corpus-errors-only") {
+        args.remove(idx);
+        config.corpus_only_errors(true);
+    }
+
+    let regression_binary = take_flag_value(&mut args, "--regression-binary");
+    if let Some(file) = take_flag_value(&mut args, "--regression-tests") {
+        if !replayable {
+            fail("--regression-tests cannot be combined with --entry, --run-tests, --file, \
+                  --symbolic-file or --short-reads");
+        }
+        let binary = regression_binary.unwrap_or_else(|| {
+            let input = args.iter().skip(1).find(|a| a.ends_with(".rs"))
+                .unwrap_or_else(|| fail("--regression-tests needs --regression-binary"));
+            ::std::path::Path::new(input).file_stem().unwrap().to_string_lossy().into_owned()
+        });
+        config.regression_tests(file, &binary);
+    }
+
+    if let Some(idx) = args.iter().position(|a| a == "--dedup-errors") {
+        args.remove(idx);
+        config.deduplicate_errors(true);
+        if !json {
+            config.findings_consumer(print_findings);
+        }
+    }
+
+    if let Some(idx) = args.iter().position(|a| a == "--coverage") {
+        args.remove(idx);
+        config.coverage_consumer(|report: ::seer::CoverageReport| {
+            for function in report.functions {
+                if function.uncovered_blocks.is_empty() {
+                    continue;
+                }
+                let blocks: Vec<String> =
+                    function.uncovered_blocks.iter().map(|bb| format!("bb{}", bb)).collect();
+                println!("not covered: {} ({} of {} blocks): {}",
+                         function.name,
+                         blocks.len(),
+                         function.total_blocks,
+                         blocks.join(", "));
+            }
+        });
+    }
+
     config.run(args);
 }
+
+fn print_test_summary(summary: ::seer::TestSummary) {
+    let status = match summary.status {
+        ::seer::ExplorationStatus::Finished => "finished",
+        ::seer::ExplorationStatus::LimitReached => "hit a limit",
+        ::seer::ExplorationStatus::Stopped => "stopped",
+    };
+    println!("test {}: {} paths explored, {} errors, {}",
+             summary.name,
+             summary.paths_explored,
+             summary.errors.len(),
+             status);
+    for error in summary.errors {
+        if let Err(e) = error.result {
+            println!("    {:?} with input {:?}", e, error.input);
+        }
+    }
+}
+
+fn print_findings(findings: Vec<::seer::Finding>) {
+    println!("found {} distinct errors", findings.len());
+    for finding in findings {
+        let location = match finding.location {
+            Some(frame) => format!("{}:{}:{}", frame.file, frame.line, frame.column),
+            None => "an unknown location".to_string(),
+        };
+        if let Err(e) = finding.example.result {
+            println!("    {:?} at {}, reached by {} paths, e.g. with input {:?}",
+                     e, location, finding.occurrences, finding.example.input);
+        }
+    }
+}
+
+fn parse_count(flag: &str, value: &str) -> usize {
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => n,
+        _ => fail(&format!("invalid value for {}: {}", flag, value)),
+    }
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("seer: {}", msg);
+    ::std::process::exit(1)
+}
+
+/// Removes `flag` and its value from `args`, accepting both `--flag value` and `--flag=value`.
+fn take_flag_value(args: &mut Vec<String>, flag: &str) -> Option<String> {
+    let prefix = format!("{}=", flag);
+    for idx in 0..args.len() {
+        if args[idx] == flag {
+            if idx + 1 >= args.len() {
+                fail(&format!("missing value for {}", flag));
+            }
+            let value = args.remove(idx + 1);
+            args.remove(idx);
+            return Some(value);
+        }
+        if args[idx].starts_with(&prefix) {
+            let value = args[idx][prefix.len()..].to_string();
+            args.remove(idx);
+            return Some(value);
+        }
+    }
+    None
+}
@@ -37,10 +37,11 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                         val,
                         dest_kind,
                         PrimVal::Bytes(1),
-                        PrimVal::Bytes(0));
+                        PrimVal::Bytes(0))?;
                     Ok(primval)
                 } else {
-                    unimplemented!()
+                    let msg = format!("unimplemented cast of input-dependent {:?} to {:?}", src_kind, dest_ty);
+                    Err(EvalError::Unimplemented(msg))
                 }
             }
             PrimVal::Undef => Ok(PrimVal::Undef),
@@ -48,8 +49,10 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
             val @ PrimVal::Bytes(_) => {
                 use super::PrimValKind::*;
                 match src_kind {
-                    F32 => unimplemented!(),//self.cast_from_float(val.to_f32()?, dest_ty),
-                    F64 => unimplemented!(),//self.cast_from_float(val.to_f64()?, dest_ty),
+                    F32 | F64 => {
+                        let msg = format!("unimplemented cast of {:?} to {:?}", src_kind, dest_ty);
+                        Err(EvalError::Unimplemented(msg))
+                    }
 
                     I8 | I16 | I32 | I64 | I128 => {
                         self.cast_from_signed_int(val.to_i128()?, dest_ty)
@@ -0,0 +1,291 @@
+//! A cache of solver answers, along the lines of KLEE's counterexample cache.
+//!
+//! Forked siblings ask z3 nearly the same questions over and over. A query whose answer
+//! follows from an earlier one is answered here:
+//!
+//! * the same constraint set has the same answer,
+//! * a subset of a satisfiable constraint set is satisfiable,
+//! * a superset of an unsatisfiable constraint set is unsatisfiable,
+//! * a constraint set that a remembered model satisfies is satisfiable.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use rustc::mir;
+
+use memory::SByte;
+use value::{PrimVal, PrimValKind};
+
+use super::{Constraint, NumericIntrinsic, PathConstraint};
+
+/// How many answered queries to remember. Lookups that are not exact repeats are linear in
+/// this.
+const CAPACITY: usize = 256;
+
+pub(super) struct QueryCache {
+    entries: VecDeque<Entry>,
+    /// The answer to every query in `entries`, keyed by its `normalize`d constraint set.
+    answers: HashMap<Vec<PathConstraint>, bool>,
+}
+
+struct Entry {
+    key: Vec<PathConstraint>,
+    constraints: HashSet<PathConstraint>,
+    satisfiable: bool,
+    model: Option<Model>,
+}
+
+/// Values z3 assigned to the variables of a satisfiable query.
+pub(super) struct Model {
+    values: HashMap<u32, ModelValue>,
+}
+
+#[derive(Clone, Copy)]
+pub(super) enum ModelValue {
+    Bool(bool),
+    Byte(u8),
+}
+
+impl QueryCache {
+    pub fn new() -> Self {
+        QueryCache {
+            entries: VecDeque::new(),
+            answers: HashMap::new(),
+        }
+    }
+
+    pub fn lookup(&self, query: &[PathConstraint]) -> Option<bool> {
+        let key = normalize(query);
+        if let Some(&answer) = self.answers.get(&key) {
+            return Some(answer);
+        }
+
+        let query_set: HashSet<PathConstraint> = key.into_iter().collect();
+        for entry in &self.entries {
+            if entry.satisfiable {
+                if query_set.len() <= entry.constraints.len() &&
+                    query_set.iter().all(|c| entry.constraints.contains(c))
+                {
+                    return Some(true);
+                }
+            } else if entry.constraints.len() <= query_set.len() &&
+                entry.constraints.iter().all(|c| query_set.contains(c))
+            {
+                return Some(false);
+            }
+        }
+
+        for entry in &self.entries {
+            if let Some(ref model) = entry.model {
+                if query.iter().all(|c| model.eval_path_constraint(*c) == Some(true)) {
+                    return Some(true);
+                }
+            }
+        }
+
+        None
+    }
+
+    pub fn insert(&mut self, query: &[PathConstraint], satisfiable: bool, model: Option<Model>) {
+        if self.entries.len() >= CAPACITY {
+            if let Some(evicted) = self.entries.pop_back() {
+                self.answers.remove(&evicted.key);
+            }
+        }
+        let key = normalize(query);
+        self.answers.insert(key.clone(), satisfiable);
+        self.entries.push_front(Entry {
+            key,
+            constraints: query.iter().cloned().collect(),
+            satisfiable,
+            model,
+        });
+    }
+}
+
+impl Model {
+    pub fn new() -> Self {
+        Model { values: HashMap::new() }
+    }
+
+    pub fn insert(&mut self, var: u32, value: ModelValue) {
+        self.values.insert(var, value);
+    }
+
+    fn eval_path_constraint(&self, constraint: PathConstraint) -> Option<bool> {
+        if let Some(guard) = constraint.guard {
+            match self.values.get(&guard.0) {
+                Some(&ModelValue::Bool(false)) => return Some(true),
+                Some(&ModelValue::Bool(true)) => {}
+                _ => return None,
+            }
+        }
+        self.eval_constraint(constraint.constraint)
+    }
+
+    /// Evaluates a constraint with the same semantics `constraint_to_ast` gives it. Returns
+    /// `None` whenever the answer is not certain, e.g. for unassigned variables, arrays, or
+    /// operations whose z3 semantics differ from Rust's.
+    fn eval_constraint(&self, constraint: Constraint) -> Option<bool> {
+        match constraint {
+            Constraint::Binop { operator, kind, rhs_operand1, rhs_operand2, lhs, lhs_kind } => {
+                let left = self.eval_primval(rhs_operand1, kind)?;
+                let right = self.eval_primval(rhs_operand2, kind)?;
+                let result = eval_binop(operator, left, right, kind)?;
+                Some(self.eval_primval(lhs, lhs_kind)? == result)
+            }
+            Constraint::Unop { operator, kind, operand, lhs } => {
+                let val = self.eval_primval(operand, kind)?;
+                let result = match operator {
+                    mir::UnOp::Not => !val & mask(kind)?,
+                    mir::UnOp::Neg => val.wrapping_neg() & mask(kind)?,
+                };
+                Some(self.eval_primval(lhs, kind)? == result)
+            }
+            Constraint::NumericIntrinsic { operator, kind, operand, lhs } => {
+                let val = self.eval_primval(operand, kind)?;
+                let bits = bits(kind)?;
+                let result = match operator {
+                    NumericIntrinsic::Ctpop => val.count_ones() as u128,
+                    NumericIntrinsic::Ctlz => (val.leading_zeros() - (128 - bits)) as u128,
+                    NumericIntrinsic::Cttz => ::std::cmp::min(val.trailing_zeros(), bits) as u128,
+                };
+                Some(self.eval_primval(lhs, kind)? == result)
+            }
+            Constraint::Compare { op, kind, lhs, rhs } => {
+                let left = self.eval_primval(lhs, kind)?;
+                let right = self.eval_primval(rhs, kind)?;
+                match op {
+                    mir::BinOp::Eq | mir::BinOp::Ne |
+                    mir::BinOp::Lt | mir::BinOp::Le |
+                    mir::BinOp::Gt | mir::BinOp::Ge => {
+                        eval_binop(op, left, right, kind).map(|r| r == 1)
+                    }
+                    _ => None,
+                }
+            }
+            Constraint::IfThenElse { discriminant, kind, then_branch, else_branch, lhs } => {
+                let result = if self.eval_primval(discriminant, PrimValKind::Bool)? == 1 {
+                    self.eval_primval(then_branch, kind)?
+                } else {
+                    self.eval_primval(else_branch, kind)?
+                };
+                Some(self.eval_primval(lhs, kind)? == result)
+            }
+            Constraint::ArrayElement { .. } | Constraint::ArrayStore { .. } => None,
+        }
+    }
+
+    fn eval_primval(&self, primval: PrimVal, kind: PrimValKind) -> Option<u128> {
+        match primval {
+            PrimVal::Bytes(v) => {
+                if let PrimValKind::Bool = kind {
+                    Some((v != 0) as u128)
+                } else {
+                    Some(v & mask(kind)?)
+                }
+            }
+            PrimVal::Abstract(sbytes) => {
+                if let PrimValKind::Bool = kind {
+                    match sbytes[0] {
+                        SByte::Abstract(var) => match self.values.get(&var.0) {
+                            Some(&ModelValue::Bool(b)) => Some(b as u128),
+                            _ => None,
+                        },
+                        SByte::Concrete(_) => None,
+                    }
+                } else {
+                    let num_bytes = bits(kind)? as usize / 8;
+                    let mut result = 0u128;
+                    for (idx, sbyte) in sbytes[..num_bytes].iter().enumerate() {
+                        let byte = match *sbyte {
+                            SByte::Concrete(b) => b,
+                            SByte::Abstract(var) => match self.values.get(&var.0) {
+                                Some(&ModelValue::Byte(b)) => b,
+                                _ => return None,
+                            },
+                        };
+                        result |= (byte as u128) << (8 * idx);
+                    }
+                    Some(result)
+                }
+            }
+            PrimVal::Ptr(_) | PrimVal::Undef => None,
+        }
+    }
+}
+
+/// The constraints of `query` in an order that does not depend on the order of `query`, and
+/// without duplicates, so that the same set of constraints always gives the same key.
+fn normalize(query: &[PathConstraint]) -> Vec<PathConstraint> {
+    let mut hashed: Vec<(u64, PathConstraint)> = query.iter().map(|&c| {
+        let mut hasher = DefaultHasher::new();
+        c.hash(&mut hasher);
+        (hasher.finish(), c)
+    }).collect();
+    hashed.sort_by_key(|&(hash, _)| hash);
+    hashed.dedup_by(|a, b| a.1 == b.1);
+    hashed.into_iter().map(|(_, c)| c).collect()
+}
+
+fn bits(kind: PrimValKind) -> Option<u32> {
+    use value::PrimValKind::*;
+    match kind {
+        Bool => Some(1),
+        U8 | I8 => Some(8),
+        U16 | I16 => Some(16),
+        U32 | I32 | Char => Some(32),
+        U64 | I64 => Some(64),
+        _ => None,
+    }
+}
+
+fn mask(kind: PrimValKind) -> Option<u128> {
+    bits(kind).map(|bits| (1u128 << bits) - 1)
+}
+
+fn sign_extend(val: u128, kind: PrimValKind) -> Option<i128> {
+    let shift = 128 - bits(kind)?;
+    Some(((val << shift) as i128) >> shift)
+}
+
+/// Mirrors `mir_binop_to_ast`. Comparisons yield 0 or 1.
+fn eval_binop(op: mir::BinOp, left: u128, right: u128, kind: PrimValKind) -> Option<u128> {
+    use rustc::mir::BinOp::*;
+
+    let mask = mask(kind)?;
+    let signed = kind.is_signed_int();
+    let compare = |unsigned: bool, signed_result: bool| {
+        Some((if signed { signed_result } else { unsigned }) as u128)
+    };
+    let sleft = sign_extend(left, kind)?;
+    let sright = sign_extend(right, kind)?;
+
+    match op {
+        Eq => Some((left == right) as u128),
+        Ne => Some((left != right) as u128),
+        Lt => compare(left < right, sleft < sright),
+        Le => compare(left <= right, sleft <= sright),
+        Gt => compare(left > right, sleft > sright),
+        Ge => compare(left >= right, sleft >= sright),
+        Add => Some(left.wrapping_add(right) & mask),
+        Sub => Some(left.wrapping_sub(right) & mask),
+        Mul => Some(left.wrapping_mul(right) & mask),
+        BitXor => Some(left ^ right),
+        BitAnd => Some(left & right),
+        BitOr => Some(left | right),
+        // z3 defines shifts by the full width or more, and division by zero, differently
+        // from Rust. Leave those to the solver.
+        Shl | Shr if right >= bits(kind)? as u128 => None,
+        Shl => Some((left << right) & mask),
+        Shr if signed => Some((sleft >> right) as u128 & mask),
+        Shr => Some(left >> right),
+        Div | Rem if right == 0 => None,
+        Div if signed => Some(sleft.wrapping_div(sright) as u128 & mask),
+        Div => Some(left / right),
+        Rem if signed => Some(sleft.wrapping_rem(sright) as u128 & mask),
+        Rem => Some(left % right),
+        _ => None,
+    }
+}
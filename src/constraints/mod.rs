@@ -0,0 +1,1417 @@
+use rustc::mir;
+use rustc::ty::Ty;
+use z3;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use error::{EvalError, EvalResult};
+use memory::{AbstractVariable, MemoryPointer, PointerOffset, SByte};
+use value::{PrimVal, PrimValKind};
+use format_executor::DebugFormatter;
+
+mod cache;
+
+use self::cache::{Model, ModelValue, QueryCache};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum NumericIntrinsic {
+    Ctpop,
+    Ctlz,
+    Cttz,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum VarType {
+    Bool,
+    BitVec8,
+    Array, // Array of BitVec8, indexed by BitVec64?
+}
+
+/// How many variables of which type make up a fresh abstract value of the given kind.
+fn layout_of_kind<'tcx>(kind: PrimValKind) -> EvalResult<'tcx, (usize, VarType)> {
+    use value::PrimValKind::*;
+    match kind {
+        Bool => Ok((1, VarType::Bool)),
+        U8 | I8 => Ok((1, VarType::BitVec8)),
+        U16 | I16 => Ok((2, VarType::BitVec8)),
+        U32 | I32 | Char => Ok((4, VarType::BitVec8)),
+        U64 | I64 => Ok((8, VarType::BitVec8)),
+        _ => Err(EvalError::Unimplemented(
+            format!("symbolic values of kind {:?}", kind))),
+    }
+}
+
+/// The solver has no encoding for pointers or undefined bytes.
+fn check_operand<'tcx>(val: PrimVal) -> EvalResult<'tcx> {
+    match val {
+        PrimVal::Bytes(_) | PrimVal::Abstract(_) => Ok(()),
+        PrimVal::Ptr(_) => Err(EvalError::Unimplemented(
+            "symbolic arithmetic on a pointer".to_string())),
+        PrimVal::Undef => Err(EvalError::ReadUndefBytes),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct SymbolicVar<'tcx> {
+    label: String,
+    /// One SymbolicVar is built out of many BitVec8's. The IDs are stored here.
+    variables: Vec<(u32, VarType)>,
+    ty: Option<Ty<'tcx>>,
+    format: VarFormat,
+}
+
+/// How the assignments of a `SymbolicVar` are reported.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum VarFormat {
+    /// As they are, and formatted according to the type of the variable if it has one.
+    Value,
+    /// As a string that ends at the first NUL byte, if there is one. The assignments are cut
+    /// off there as well.
+    String,
+    /// Like `String`, after a first byte that is 1 if the string is there at all. Without
+    /// the string, there are no assignments and no string.
+    OptionalString,
+    /// Bytes of any value after their length, a little-endian `u64`. The assignments are cut
+    /// off at that length, and shown as a string if they are UTF-8.
+    Buffer,
+}
+
+#[derive(Clone, Debug)]
+pub struct ConstraintContext<'tcx> {
+    /// Shared by every path of a run, so that variable ids never collide between paths that
+    /// might later be merged.
+    next_id: Rc<Cell<u32>>,
+    /// Each entry represents a variable as an ID and a type. These are used
+    /// for intermediate results in constraints and are not displayed.
+    variables_inner: Vec<(u32, VarType)>,
+    /// Index 0 is stdin
+    symbolic_vars: Vec<SymbolicVar<'tcx>>,
+    constraints: Vec<PathConstraint>,
+    /// Groups `constraints` by the variables they share, extended as constraints are pushed.
+    components: Components,
+}
+
+/// A constraint on the current path. After two paths are merged, the constraints that only
+/// one of them had are kept under a guard: they need to hold only if the guard is true.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+struct PathConstraint {
+    guard: Option<AbstractVariable>,
+    constraint: Constraint,
+}
+
+impl PathConstraint {
+    fn for_each_variable<F>(&self, mut f: F) where F: FnMut(u32) {
+        if let Some(guard) = self.guard {
+            f(guard.0);
+        }
+        self.constraint.for_each_variable(f);
+    }
+}
+
+impl From<Constraint> for PathConstraint {
+    fn from(constraint: Constraint) -> Self {
+        PathConstraint { guard: None, constraint }
+    }
+}
+
+/// Holds relevant parts of the solution z3 found when solving a set of constraints.
+#[derive(Clone)]
+pub struct SatisfiedVar {
+    pub label: String,
+    /// Variable assignments that satisfy the given constraints.
+    pub assignments: Vec<u8>,
+    pub assignments_str: Option<String>,
+}
+
+impl fmt::Debug for SatisfiedVar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.assignments_str {
+            Some(ref s) => {
+                write!(f, "{}: {:?}", self.label, s)
+            }
+            None => {
+                write!(f, "{}: {:?}", self.label, self.assignments)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Constraint {
+    Binop {
+        operator: mir::BinOp,
+        kind: PrimValKind,
+        rhs_operand1: PrimVal,
+        rhs_operand2: PrimVal,
+        lhs: PrimVal,
+        lhs_kind: PrimValKind,
+    },
+
+    // lhs = op(rhs)
+    Unop {
+        operator: mir::UnOp,
+        kind: PrimValKind,
+        operand: PrimVal,
+        lhs: PrimVal,
+    },
+
+    Compare { op: mir::BinOp, kind: PrimValKind, lhs: PrimVal, rhs: PrimVal, },
+
+    NumericIntrinsic {
+        operator: NumericIntrinsic,
+        kind: PrimValKind,
+        operand: PrimVal,
+        lhs: PrimVal,
+    },
+
+    // lhs = if discriminant then then_branch else else_branch
+    IfThenElse {
+        discriminant: PrimVal,
+        kind: PrimValKind,
+        then_branch: PrimVal,
+        else_branch: PrimVal,
+        lhs: PrimVal,
+    },
+
+    // array[index] = value
+    ArrayElement {
+        array: AbstractVariable,
+        index: PrimVal,
+        value: SByte,
+    },
+
+    // lhs = array.store(idx, value)
+    ArrayStore {
+        array: AbstractVariable,
+        index: PrimVal,
+        value: SByte,
+        lhs: AbstractVariable,
+    }
+}
+
+// `mir::BinOp` and `mir::UnOp` are not `Hash`, so only the operands are hashed.
+impl Hash for Constraint {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        ::std::mem::discriminant(self).hash(state);
+        match *self {
+            Constraint::Binop { rhs_operand1, rhs_operand2, lhs, .. } => {
+                rhs_operand1.hash(state);
+                rhs_operand2.hash(state);
+                lhs.hash(state);
+            }
+            Constraint::Unop { operand, lhs, .. } |
+            Constraint::NumericIntrinsic { operand, lhs, .. } => {
+                operand.hash(state);
+                lhs.hash(state);
+            }
+            Constraint::Compare { lhs, rhs, .. } => {
+                lhs.hash(state);
+                rhs.hash(state);
+            }
+            Constraint::IfThenElse { discriminant, then_branch, else_branch, lhs, .. } => {
+                discriminant.hash(state);
+                then_branch.hash(state);
+                else_branch.hash(state);
+                lhs.hash(state);
+            }
+            Constraint::ArrayElement { array, index, value } => {
+                array.hash(state);
+                index.hash(state);
+                value.hash(state);
+            }
+            Constraint::ArrayStore { array, index, value, lhs } => {
+                array.hash(state);
+                index.hash(state);
+                value.hash(state);
+                lhs.hash(state);
+            }
+        }
+    }
+}
+
+impl Constraint {
+    pub fn new_binop(
+        operator: mir::BinOp,
+        kind: PrimValKind,
+        rhs_operand1: PrimVal,
+        rhs_operand2: PrimVal,
+        lhs: PrimVal,
+        lhs_kind: PrimValKind,
+    ) -> Self {
+        Constraint::Binop {
+            operator, kind, rhs_operand1, rhs_operand2, lhs, lhs_kind,
+        }
+    }
+
+    pub fn new_unop(
+        operator: mir::UnOp,
+        kind: PrimValKind,
+        operand: PrimVal,
+        lhs: PrimVal,
+    ) -> Self {
+        Constraint::Unop {
+            operator, kind, operand, lhs,
+        }
+    }
+
+    pub fn new_intrinsic(
+        operator: NumericIntrinsic,
+        kind: PrimValKind,
+        operand: PrimVal,
+        lhs: PrimVal,
+    ) -> Self {
+        Constraint::NumericIntrinsic {
+            operator, kind, operand, lhs,
+        }
+    }
+
+    pub fn new_compare(op: mir::BinOp, kind: PrimValKind, lhs: PrimVal, rhs: PrimVal) -> Self {
+        Constraint::Compare { op, kind, lhs, rhs }
+    }
+
+    /// Calls `f` with the id of every abstract variable that this constraint mentions.
+    fn for_each_variable<F>(&self, mut f: F) where F: FnMut(u32) {
+        fn sbyte<F: FnMut(u32)>(b: SByte, f: &mut F) {
+            if let SByte::Abstract(v) = b {
+                f(v.0);
+            }
+        }
+        fn primval<F: FnMut(u32)>(v: PrimVal, f: &mut F) {
+            match v {
+                PrimVal::Abstract(sbytes) |
+                PrimVal::Ptr(MemoryPointer {
+                    offset: PointerOffset::Abstract(sbytes), ..
+                }) => {
+                    for b in sbytes.iter() {
+                        sbyte(*b, f);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        match *self {
+            Constraint::Binop { rhs_operand1, rhs_operand2, lhs, .. } => {
+                primval(rhs_operand1, &mut f);
+                primval(rhs_operand2, &mut f);
+                primval(lhs, &mut f);
+            }
+            Constraint::Unop { operand, lhs, .. } |
+            Constraint::NumericIntrinsic { operand, lhs, .. } => {
+                primval(operand, &mut f);
+                primval(lhs, &mut f);
+            }
+            Constraint::Compare { lhs, rhs, .. } => {
+                primval(lhs, &mut f);
+                primval(rhs, &mut f);
+            }
+            Constraint::IfThenElse { discriminant, then_branch, else_branch, lhs, .. } => {
+                primval(discriminant, &mut f);
+                primval(then_branch, &mut f);
+                primval(else_branch, &mut f);
+                primval(lhs, &mut f);
+            }
+            Constraint::ArrayElement { array, index, value } => {
+                f(array.0);
+                primval(index, &mut f);
+                sbyte(value, &mut f);
+            }
+            Constraint::ArrayStore { array, index, value, lhs } => {
+                f(array.0);
+                primval(index, &mut f);
+                sbyte(value, &mut f);
+                f(lhs.0);
+            }
+        }
+    }
+}
+
+impl<'tcx> ConstraintContext<'tcx> {
+    pub fn new() -> Self {
+        ConstraintContext {
+            next_id: Rc::new(Cell::new(0)),
+            variables_inner: Vec::new(),
+            symbolic_vars: vec![SymbolicVar {
+                label: "stdin".to_string(),
+                variables: Vec::new(),
+                ty: None,
+                format: VarFormat::Value,
+            }],
+            constraints: Vec::new(),
+            components: Components::new(),
+        }
+    }
+
+    fn next_id(&mut self) -> u32 {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        id
+    }
+
+    fn allocate_abstract_var(&mut self, var_type: VarType) -> AbstractVariable {
+        let id = self.next_id();
+        self.variables_inner.push((id, var_type));
+        AbstractVariable(id)
+    }
+
+    pub fn fresh_stdin_byte(&mut self) -> SByte {
+        let id = self.next_id();
+        self.symbolic_vars[0].variables.push((id, VarType::BitVec8));
+        SByte::Abstract(AbstractVariable(id))
+    }
+
+    /// Creates a `u64` between `min` and `max` that is not part of the input the executor
+    /// reports, like the length of stdin.
+    pub fn fresh_bounded_u64(&mut self, min: u64, max: u64) -> PrimVal {
+        let mut len = [SByte::Concrete(0); 8];
+        for idx in 0..8 {
+            len[idx] = SByte::Abstract(self.allocate_abstract_var(VarType::BitVec8));
+        }
+        let len = PrimVal::Abstract(len);
+        self.push_constraint(Constraint::new_compare(
+            mir::BinOp::Ge, PrimValKind::U64, len, PrimVal::Bytes(min as u128)));
+        self.push_constraint(Constraint::new_compare(
+            mir::BinOp::Le, PrimValKind::U64, len, PrimVal::Bytes(max as u128)));
+        len
+    }
+
+    pub fn fresh_symbolic_var(&mut self, label: String, size: u32, ty: Ty<'tcx>) -> Vec<SByte> {
+        let mut sbytes = Vec::new();
+        let mut vars = Vec::new();
+        for _ in 0..size {
+            let id = self.next_id();
+            sbytes.push(SByte::Abstract(AbstractVariable(id)));
+            vars.push((id, VarType::BitVec8));
+        }
+        self.symbolic_vars.push(SymbolicVar {
+            label: label,
+            variables: vars,
+            ty: Some(ty),
+            format: VarFormat::Value,
+        });
+        sbytes
+    }
+
+    /// Creates a symbolic string of `size` ASCII bytes, which ends at its first NUL byte if
+    /// it has one, like a command-line argument.
+    pub fn fresh_symbolic_string(&mut self, label: String, size: u32) -> Vec<SByte> {
+        self.fresh_string_var(label, size, VarFormat::String)
+    }
+
+    /// Like `fresh_symbolic_string`, but the string may be missing, like an environment
+    /// variable. The first byte returned is 1 if it is there and 0 otherwise, the rest are the
+    /// bytes of the string.
+    pub fn fresh_symbolic_optional_string(&mut self, label: String, size: u32) -> Vec<SByte> {
+        self.fresh_string_var(label, size + 1, VarFormat::OptionalString)
+    }
+
+    /// Creates up to `max_len` symbolic bytes of any value, like the contents of a file.
+    /// Returns the length, a `u64` no larger than `max_len`, and the bytes.
+    pub fn fresh_symbolic_buffer(&mut self, label: String, max_len: u32) -> (PrimVal, Vec<SByte>) {
+        let mut len = [SByte::Concrete(0); 8];
+        let mut sbytes = Vec::new();
+        let mut vars = Vec::new();
+        for idx in 0..8 + max_len as usize {
+            let id = self.next_id();
+            let sbyte = SByte::Abstract(AbstractVariable(id));
+            if idx < 8 {
+                len[idx] = sbyte;
+            } else {
+                sbytes.push(sbyte);
+            }
+            vars.push((id, VarType::BitVec8));
+        }
+        let max = PrimVal::Bytes(max_len as u128);
+        self.push_constraint(Constraint::new_compare(
+            mir::BinOp::Le, PrimValKind::U64, PrimVal::Abstract(len), max));
+        self.symbolic_vars.push(SymbolicVar {
+            label: label,
+            variables: vars,
+            ty: None,
+            format: VarFormat::Buffer,
+        });
+        (PrimVal::Abstract(len), sbytes)
+    }
+
+    fn fresh_string_var(&mut self, label: String, size: u32, format: VarFormat) -> Vec<SByte> {
+        let mut sbytes = Vec::new();
+        let mut vars = Vec::new();
+        for idx in 0..size {
+            let id = self.next_id();
+            let sbyte = SByte::Abstract(AbstractVariable(id));
+            let mut byte = [SByte::Concrete(0); 8];
+            byte[0] = sbyte;
+            let (op, bound) = if idx == 0 && format == VarFormat::OptionalString {
+                (mir::BinOp::Le, 1)
+            } else {
+                (mir::BinOp::Lt, 0x80)
+            };
+            self.push_constraint(Constraint::new_compare(
+                op, PrimValKind::U8, PrimVal::Abstract(byte), PrimVal::Bytes(bound)));
+            sbytes.push(sbyte);
+            vars.push((id, VarType::BitVec8));
+        }
+        self.symbolic_vars.push(SymbolicVar {
+            label: label,
+            variables: vars,
+            ty: None,
+            format: format,
+        });
+        sbytes
+    }
+
+    pub fn push_constraint(&mut self, constraint: Constraint) {
+        self.push_path_constraint(constraint.into());
+    }
+
+    fn push_path_constraint(&mut self, c: PathConstraint) {
+        self.components.add(self.constraints.len(), &c);
+        self.constraints.push(c);
+    }
+
+    /// Creates a fresh abstract PrimVal `X` and adds a constraint
+    /// `X == rhs_operand1 binop rhs_operand2`. Returns `X`.
+    pub fn add_binop_constraint(
+        &mut self,
+        bin_op: mir::BinOp,
+        rhs_operand1: PrimVal,
+        rhs_operand2: PrimVal,
+        kind: PrimValKind) -> EvalResult<'tcx, PrimVal> {
+
+        check_operand(rhs_operand1)?;
+        check_operand(rhs_operand2)?;
+
+        let mut buffer = [SByte::Concrete(0); 8];
+
+        let (num_bytes, var_type) = layout_of_kind(kind)?;
+        let (num_bytes, var_type, lhs_kind) = match bin_op {
+            mir::BinOp::Eq |
+            mir::BinOp::Ne |
+            mir::BinOp::Lt |
+            mir::BinOp::Le |
+            mir::BinOp::Gt |
+            mir::BinOp::Ge => (1, VarType::Bool, PrimValKind::Bool),
+            mir::BinOp::Offset => return Err(EvalError::Unimplemented(
+                "symbolic pointer offset".to_string())),
+            _ => (num_bytes, var_type, kind),
+        };
+
+        for idx in 0..num_bytes {
+            buffer[idx] = SByte::Abstract(self.allocate_abstract_var(var_type));
+        }
+
+        let primval = PrimVal::Abstract(buffer);
+
+        let constraint = Constraint::new_binop(bin_op, kind, rhs_operand1,
+                                               rhs_operand2, primval, lhs_kind);
+
+        self.push_constraint(constraint);
+
+        Ok(primval)
+    }
+
+    /// Creates a fresh abstract PrimVal `X` and adds a constraint
+    /// `X == unop right`. Returns `X`.
+    pub fn add_unop_constraint(
+        &mut self,
+        un_op: mir::UnOp,
+        val: PrimVal,
+        kind: PrimValKind) -> EvalResult<'tcx, PrimVal> {
+
+        check_operand(val)?;
+        let (num_bytes, var_type) = layout_of_kind(kind)?;
+
+        let mut buffer = [SByte::Concrete(0); 8];
+        for idx in 0..num_bytes {
+            buffer[idx] = SByte::Abstract(self.allocate_abstract_var(var_type));
+        }
+
+        let primval = PrimVal::Abstract(buffer);
+        let constraint = Constraint::new_unop(un_op, kind, val, primval);
+
+        self.push_constraint(constraint);
+
+        Ok(primval)
+    }
+
+    /// Creates a fresh abstract PrimVal `X` and adds a constraint
+    /// `X == intrinsic(val)`. Returns `X`.
+    pub fn add_intrinsic_constraint(
+        &mut self,
+        op: NumericIntrinsic,
+        val: PrimVal,
+        kind: PrimValKind) -> EvalResult<'tcx, PrimVal> {
+
+        check_operand(val)?;
+        let (num_bytes, _) = layout_of_kind(kind)?;
+
+        let mut buffer = [SByte::Concrete(0); 8];
+        for idx in 0..num_bytes {
+            buffer[idx] = SByte::Abstract(self.allocate_abstract_var(VarType::BitVec8));
+        }
+
+        let primval = PrimVal::Abstract(buffer);
+        let constraint = Constraint::new_intrinsic(op, kind, val, primval);
+
+        self.push_constraint(constraint);
+
+        Ok(primval)
+    }
+
+    pub fn add_if_then_else(
+        &mut self,
+        discriminant: PrimVal,
+        kind: PrimValKind,
+        then_branch: PrimVal,
+        else_branch: PrimVal
+    ) -> EvalResult<'tcx, PrimVal> {
+        check_operand(discriminant)?;
+        check_operand(then_branch)?;
+        check_operand(else_branch)?;
+        let (num_bytes, var_type) = layout_of_kind(kind)?;
+        let mut buffer = [SByte::Concrete(0); 8];
+        for idx in 0..num_bytes {
+            buffer[idx] = SByte::Abstract(self.allocate_abstract_var(var_type));
+        }
+
+        let lhs = PrimVal::Abstract(buffer);
+        self.push_constraint(Constraint::IfThenElse {
+            discriminant,
+            kind,
+            then_branch,
+            else_branch,
+            lhs,
+        });
+
+        Ok(lhs)
+    }
+
+    pub fn new_array(&mut self) -> AbstractVariable {
+        self.allocate_abstract_var(VarType::Array)
+    }
+
+    pub fn set_array_element_constraint(
+        &mut self,
+        array: AbstractVariable,
+        index: PrimVal,
+        value: SByte)
+    {
+        self.push_constraint(
+            Constraint::ArrayElement {
+                array, index, value,
+            });
+    }
+
+    pub fn add_array_element_constraint(
+        &mut self,
+        array: AbstractVariable,
+        index: PrimVal)
+        -> SByte
+    {
+        let value = SByte::Abstract(self.allocate_abstract_var(VarType::BitVec8));
+        self.push_constraint(
+            Constraint::ArrayElement {
+                array, index, value,
+            });
+
+        value
+    }
+
+    pub fn store_array_element(
+        &mut self,
+        array: AbstractVariable,
+        index: PrimVal,
+        value: SByte)
+        -> AbstractVariable
+    {
+        let new_array = self.new_array();
+        self.push_constraint(
+            Constraint::ArrayStore {
+                array, index, value, lhs: new_array,
+            });
+        new_array
+    }
+
+    /// The ids of all boolean variables.
+    pub fn bool_variables(&self) -> HashSet<u32> {
+        self.variables_inner.iter()
+            .chain(self.symbolic_vars.iter().flat_map(|s| s.variables.iter()))
+            .filter(|&&(_, var_type)| var_type == VarType::Bool)
+            .map(|&(id, _)| id)
+            .collect()
+    }
+
+    /// Paths can only be merged if they come from the same run and agree on all labeled
+    /// symbolic inputs, e.g. have read the same number of bytes from stdin.
+    pub fn can_merge_with(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.next_id, &other.next_id) && self.symbolic_vars == other.symbolic_vars
+    }
+
+    /// Replaces the path condition with the disjunction of the path conditions of `self` and
+    /// `other`. Returns a fresh boolean guard that is true exactly on the `self` side, for use
+    /// as the discriminant of merged values.
+    pub fn merge_path_conditions(&mut self, other: &Self) -> PrimVal {
+        let common = self.constraints.iter()
+            .zip(other.constraints.iter())
+            .take_while(|&(a, b)| a == b)
+            .count();
+
+        let guard = self.allocate_abstract_var(VarType::Bool);
+        let not_guard = self.allocate_abstract_var(VarType::Bool);
+        let ours = self.constraints.split_off(common);
+        self.components = Components::new();
+        for (idx, c) in self.constraints.iter().enumerate() {
+            self.components.add(idx, c);
+        }
+        self.push_constraint(Constraint::new_unop(
+            mir::UnOp::Not, PrimValKind::Bool, bool_var(guard), bool_var(not_guard)));
+        for c in ours {
+            self.push_guarded(guard, c);
+        }
+        for c in &other.constraints[common..] {
+            self.push_guarded(not_guard, *c);
+        }
+
+        let known: HashSet<u32> = self.variables_inner.iter().map(|&(id, _)| id).collect();
+        for &(id, var_type) in &other.variables_inner {
+            if !known.contains(&id) {
+                self.variables_inner.push((id, var_type));
+            }
+        }
+
+        bool_var(guard)
+    }
+
+    fn push_guarded(&mut self, guard: AbstractVariable, c: PathConstraint) {
+        let guard = match c.guard {
+            None => guard,
+            Some(inner) => {
+                // guard => (inner => c) is the same as (guard & inner) => c
+                let both = self.allocate_abstract_var(VarType::Bool);
+                self.push_constraint(Constraint::new_binop(
+                    mir::BinOp::BitAnd, PrimValKind::Bool,
+                    bool_var(guard), bool_var(inner), bool_var(both), PrimValKind::Bool));
+                both
+            }
+        };
+        self.push_path_constraint(PathConstraint { guard: Some(guard), constraint: c.constraint });
+    }
+
+    pub fn get_satisfying_values<T>(&self, formatter: &T) -> Vec<SatisfiedVar>
+        where T: DebugFormatter<'tcx>
+    {
+        SOLVER.with(|solver| {
+            let mut solver = solver.borrow_mut();
+            solver.sync(self, &self.constraints);
+            let ctx = solver.ctx;
+
+            let mut consts = Vec::new();
+            for v in self.variables_inner.iter() {
+                consts.push(self.variable_to_ast(ctx, *v));
+            }
+
+            // Each SymbolicVar has its internal variables mapped to z3 ASTs. Keep the labels
+            // and types.
+            let result_consts = self.symbolic_vars.iter().map(|g| {
+                let asts = g.variables.iter().map(|v| self.variable_to_ast(ctx, *v));
+                (g.label.clone(), asts, g.ty, g.format)
+            });
+
+            assert!(solver.check());
+            let model = solver.solver.get_model();
+
+            let mut result = Vec::new();
+            for (label, asts, ty_opt, format) in result_consts {
+                let mut assignments: Vec<u8> = asts.map(
+                        |ast| model.eval(&ast).unwrap().as_u64().unwrap() as u8)
+                        .collect();
+                let mut present = true;
+                if format == VarFormat::OptionalString {
+                    present = assignments.remove(0) != 0;
+                    if !present {
+                        assignments.clear();
+                    }
+                }
+                if format == VarFormat::Buffer {
+                    let len = assignments.drain(..8).rev().fold(0, |len, b| len << 8 | b as usize);
+                    assignments.truncate(len);
+                }
+                let assignments_str = match (format, ty_opt) {
+                    (VarFormat::Buffer, _) => String::from_utf8(assignments.clone()).ok(),
+                    (VarFormat::OptionalString, _) if !present => None,
+                    (VarFormat::String, _) | (VarFormat::OptionalString, _) => {
+                        if let Some(end) = assignments.iter().position(|&b| b == 0) {
+                            assignments.truncate(end);
+                        }
+                        Some(String::from_utf8_lossy(&assignments).into_owned())
+                    }
+                    (VarFormat::Value, Some(ty)) => {
+                        let s_res = formatter.debug_repr(&assignments, ty);
+                        s_res.ok()
+                    }
+                    (VarFormat::Value, None) => None,
+                };
+                result.push(SatisfiedVar {
+                    label: label,
+                    assignments: assignments,
+                    assignments_str: assignments_str,
+                });
+            }
+
+            result
+        })
+    }
+
+    /// Checks whether the constraints of this path, together with `constraints`, can be
+    /// satisfied. Only the constraints that z3 has not seen yet are sent to it.
+    ///
+    /// The path constraints are assumed to be satisfiable on their own, so only those that
+    /// transitively share a variable with `constraints` can make the query unsatisfiable.
+    /// The cache is keyed by just those. The solver still gets the whole path, since the
+    /// slices of consecutive queries rarely share a prefix, while the paths they come from do.
+    pub fn is_feasible_with(
+        &self,
+        constraints: &[Constraint])
+        -> bool
+    {
+        if constraints.is_empty() {
+            return true;
+        }
+
+        let relevant = self.independent_slice(constraints);
+        let mut query = relevant.clone();
+        query.extend(constraints.iter().map(|&c| PathConstraint::from(c)));
+
+        if let Some(answer) = QUERY_CACHE.with(|cache| cache.borrow().lookup(&query)) {
+            return answer;
+        }
+
+        let (answer, model) = SOLVER.with(|solver| {
+            let mut solver = solver.borrow_mut();
+            solver.sync(self, &self.constraints);
+
+            solver.solver.push();
+            for c in constraints {
+                let ast = self.constraint_to_ast(solver.ctx, *c);
+                solver.solver.assert(&ast);
+            }
+            let answer = solver.check();
+            let model = if answer {
+                self.extract_model(solver.ctx, &solver.solver.get_model(), &query)
+            } else {
+                None
+            };
+            solver.solver.pop(1);
+            (answer, model)
+        });
+
+        QUERY_CACHE.with(|cache| cache.borrow_mut().insert(&query, answer, model));
+        answer
+    }
+
+    /// Returns, in increasing order, every value up to `max` that `value` can take on this
+    /// path. Makes one solver query per value found, plus one.
+    pub fn possible_values(
+        &self,
+        value: PrimVal,
+        kind: PrimValKind,
+        max: u128)
+        -> Vec<u128>
+    {
+        self.possible_values_up_to(value, kind, max, usize::max_value())
+    }
+
+    /// Like `possible_values`, but stops looking once `limit` values have been found.
+    fn possible_values_up_to(
+        &self,
+        value: PrimVal,
+        kind: PrimValKind,
+        max: u128,
+        limit: usize)
+        -> Vec<u128>
+    {
+        let bound = Constraint::new_compare(mir::BinOp::Le, kind, value, PrimVal::Bytes(max));
+
+        let mut values = SOLVER.with(|solver| {
+            let mut solver = solver.borrow_mut();
+            solver.sync(self, &self.constraints);
+            let ctx = solver.ctx;
+
+            solver.solver.push();
+            solver.solver.assert(&self.constraint_to_ast(ctx, bound));
+            let value_ast = self.primval_to_ast(ctx, value, kind);
+
+            let mut values = Vec::new();
+            while values.len() < limit && solver.check() {
+                let found = match solver.solver.get_model().eval(&value_ast).and_then(|v| v.as_u64()) {
+                    Some(found) => found as u128,
+                    None => break,
+                };
+                values.push(found);
+                let excluded = Constraint::new_compare(
+                    mir::BinOp::Ne, kind, value, PrimVal::Bytes(found));
+                solver.solver.assert(&self.constraint_to_ast(ctx, excluded));
+            }
+            solver.solver.pop(1);
+            values
+        });
+
+        values.sort();
+        values
+    }
+
+    /// Returns the only value that `value` can take on this path, if there is one and it is
+    /// at most `max`. Otherwise returns every value up to `max` that it can take.
+    pub fn unique_value(
+        &self,
+        value: PrimVal,
+        kind: PrimValKind,
+        max: u128)
+        -> Result<u128, Vec<u128>>
+    {
+        self.unique_value_up_to(value, kind, max, usize::max_value())
+    }
+
+    /// Like `unique_value`, but looks for at most `limit` values. If it finds that many, there
+    /// may be more.
+    pub fn unique_value_up_to(
+        &self,
+        value: PrimVal,
+        kind: PrimValKind,
+        max: u128,
+        limit: usize)
+        -> Result<u128, Vec<u128>>
+    {
+        let values = self.possible_values_up_to(value, kind, max, limit);
+        if values.len() == 1 {
+            let other = Constraint::new_compare(
+                mir::BinOp::Ne, kind, value, PrimVal::Bytes(values[0]));
+            if !self.is_feasible_with(&[other]) {
+                return Ok(values[0]);
+            }
+        }
+        Err(values)
+    }
+
+    /// Reads the values of the variables in `query` out of a z3 model, so that the cache can
+    /// try them on later queries. Gives up on queries involving arrays.
+    fn extract_model<'a>(
+        &self,
+        ctx: &'a z3::Context,
+        model: &z3::Model<'a>,
+        query: &[PathConstraint])
+        -> Option<Model>
+    {
+        let mut wanted = HashMap::new();
+        for c in query {
+            c.for_each_variable(|v| { wanted.insert(v, None); });
+        }
+        let all_vars = self.variables_inner.iter()
+            .chain(self.symbolic_vars.iter().flat_map(|s| s.variables.iter()));
+        for &(id, var_type) in all_vars {
+            if let Some(entry) = wanted.get_mut(&id) {
+                *entry = Some(var_type);
+            }
+        }
+
+        let mut result = Model::new();
+        for (id, var_type) in wanted {
+            let value = match var_type {
+                Some(VarType::Bool) => {
+                    let ast = self.variable_to_ast(ctx, (id, VarType::Bool));
+                    ModelValue::Bool(model.eval(&ast)?.as_bool()?)
+                }
+                Some(VarType::BitVec8) => {
+                    let ast = self.variable_to_ast(ctx, (id, VarType::BitVec8));
+                    ModelValue::Byte(model.eval(&ast)?.as_u64()? as u8)
+                }
+                Some(VarType::Array) | None => return None,
+            };
+            result.insert(id, value);
+        }
+        Some(result)
+    }
+
+    /// Returns, in their original order, the path constraints that are connected to
+    /// `constraints` through shared abstract variables.
+    fn independent_slice(&self, constraints: &[Constraint]) -> Vec<PathConstraint> {
+        let mut roots = HashSet::new();
+        for c in constraints {
+            c.for_each_variable(|v| { roots.insert(self.components.find(v)); });
+        }
+        let mut indices: Vec<usize> = roots.iter()
+            .filter_map(|root| self.components.constraints.get(root))
+            .flat_map(|indices| indices.iter().cloned())
+            .collect();
+        indices.sort();
+        indices.into_iter().map(|idx| self.constraints[idx]).collect()
+    }
+
+    fn path_constraint_to_ast<'a>(
+        &self,
+        ctx: &'a z3::Context,
+        constraint: PathConstraint)
+        -> z3::Ast<'a>
+    {
+        let ast = self.constraint_to_ast(ctx, constraint.constraint);
+        match constraint.guard {
+            Some(guard) => ctx.numbered_bool_const(guard.0).not().or(&[&ast]),
+            None => ast,
+        }
+    }
+
+    fn variable_to_ast<'a>(
+        &self,
+        ctx: &'a z3::Context,
+        variable: (u32, VarType))
+        -> z3::Ast<'a>
+    {
+        let (idx, var_type) = variable;
+        match var_type {
+            VarType::Bool => {
+                ctx.numbered_bool_const(idx as u32)
+            }
+            VarType::BitVec8 => {
+                ctx.numbered_bitvector_const(idx as u32, 8)
+            }
+            VarType::Array => {
+                ::z3::Ast::new_const(
+                    &::z3::Symbol::from_int(&ctx, idx as u32),
+                    &ctx.array_sort(
+                        &ctx.bitvector_sort(64),
+                        &ctx.bitvector_sort(8)))
+            }
+        }
+    }
+
+    fn sbyte_to_ast<'a>(
+        &self,
+        ctx: &'a z3::Context,
+        sbyte: SByte)
+        -> z3::Ast<'a>
+    {
+        match sbyte {
+            SByte::Abstract(b) => {
+                ctx.numbered_bitvector_const(b.0, 8)
+            }
+            SByte::Concrete(b) => {
+                z3::Ast::bv_from_u64(&ctx, b as u64, 8)
+            }
+        }
+    }
+
+    fn sbyte_slice_to_ast<'a>(
+        &self,
+        ctx: &'a z3::Context,
+        sbytes: &[SByte])
+        -> z3::Ast<'a>
+    {
+        if sbytes.is_empty() {
+            panic!("expected non-empty sbyte slice");
+        } else if sbytes.len() == 1 {
+            self.sbyte_to_ast(ctx, sbytes[0])
+        } else {
+            let mut result = self.sbyte_to_ast(ctx, sbytes[0]);
+            for sbyte in &sbytes[1..] {
+                result = self.sbyte_to_ast(ctx, *sbyte).concat(&result);
+            }
+
+            result
+        }
+    }
+
+    fn constraint_to_ast<'a>(
+        &self,
+        ctx: &'a z3::Context,
+        constraint: Constraint)
+        -> z3::Ast<'a>
+    {
+        match constraint {
+            Constraint::Binop { operator, kind, lhs, rhs_operand1,
+                                rhs_operand2, lhs_kind } => {
+                self.primval_to_ast(&ctx, lhs, lhs_kind)._eq(
+                    &self.mir_binop_to_ast(
+                        &ctx,
+                        operator,
+                        self.primval_to_ast(&ctx, rhs_operand1, kind),
+                        self.primval_to_ast(&ctx, rhs_operand2, kind),
+                        kind))
+            }
+            Constraint::Unop { operator, kind, lhs, operand, .. } => {
+                self.primval_to_ast(&ctx, lhs, kind)._eq(
+                    &self.mir_unop_to_ast(
+                        &ctx,
+                        operator,
+                        self.primval_to_ast(&ctx, operand, kind)))
+            }
+
+            Constraint::NumericIntrinsic { operator, kind, lhs, operand, .. } => {
+                self.primval_to_ast(&ctx, lhs, kind)._eq(
+                    &self.mir_intrinsic_to_ast(
+                        &ctx,
+                        operator,
+                        self.primval_to_ast(&ctx, operand, kind),
+                        kind))
+            }
+
+            Constraint::Compare { op, lhs, rhs, kind, .. } => {
+                self.mir_binop_to_ast(
+                    &ctx,
+                    op,
+                    self.primval_to_ast(&ctx, lhs, kind),
+                    self.primval_to_ast(&ctx, rhs, kind),
+                    kind)
+            }
+
+            Constraint::IfThenElse { discriminant, kind, then_branch, else_branch, lhs } => {
+                self.primval_to_ast(&ctx, lhs, kind)._eq(
+                    &self.primval_to_ast(&ctx, discriminant, PrimValKind::Bool).ite(
+                        &self.primval_to_ast(&ctx, then_branch, kind),
+                        &self.primval_to_ast(&ctx, else_branch, kind)))
+
+            }
+
+            Constraint::ArrayElement { array, index, value, } => {
+                let c = ::z3::Ast::new_const(
+                    &::z3::Symbol::from_int(ctx, array.0),
+                    &ctx.array_sort(
+                        &ctx.bitvector_sort(64),
+                        &ctx.bitvector_sort(8)));
+
+                c.select(&self.primval_to_ast(ctx, index, PrimValKind::U64))._eq(
+                    &self.sbyte_to_ast(ctx, value))
+            }
+
+            Constraint::ArrayStore { array, index, value, lhs } => {
+                let c0 = ::z3::Ast::new_const(
+                    &::z3::Symbol::from_int(ctx, array.0),
+                    &ctx.array_sort(
+                        &ctx.bitvector_sort(64),
+                        &ctx.bitvector_sort(8)));
+
+                let c1 = ::z3::Ast::new_const(
+                    &::z3::Symbol::from_int(ctx, lhs.0),
+                    &ctx.array_sort(
+                        &ctx.bitvector_sort(64),
+                        &ctx.bitvector_sort(8)));
+
+                c1._eq(
+                    &c0.store(
+                        &self.primval_to_ast(ctx, index, PrimValKind::U64),
+                        &self.sbyte_to_ast(ctx, value)))
+            }
+
+        }
+    }
+
+    fn primval_to_ast<'a>(
+        &self,
+        ctx: &'a z3::Context,
+        primval: PrimVal,
+        kind: PrimValKind)
+        -> z3::Ast<'a>
+    {
+        match primval {
+            PrimVal::Undef => {
+                bug!("undefined bytes in a solver constraint")
+            }
+            PrimVal::Ptr(_) => {
+                bug!("pointer in a solver constraint")
+            }
+            PrimVal::Abstract(sbytes) => {
+                if let PrimValKind::Bool = kind {
+                    match sbytes[0] {
+                        SByte::Abstract(b) => {
+                            ctx.numbered_bool_const(b.0)
+                        }
+                        SByte::Concrete(b) => {
+                            z3::Ast::from_bool(&ctx, b != 0)
+                        }
+                    }
+                } else {
+                    let num_bytes = kind.num_bytes();
+                    self.sbyte_slice_to_ast(ctx, &sbytes[..num_bytes])
+                }
+            }
+            PrimVal::Bytes(v) => {
+                match kind {
+                    PrimValKind::Bool => z3::Ast::from_bool(&ctx, v != 0),
+                    PrimValKind::U8 | PrimValKind::I8 => z3::Ast::bv_from_u64(&ctx, v as u64, 8),
+                    PrimValKind::U16 | PrimValKind::I16 => z3::Ast::bv_from_u64(&ctx, v as u64, 16),
+                    PrimValKind::U32 | PrimValKind::I32 => z3::Ast::bv_from_u64(&ctx, v as u64, 32),
+                    PrimValKind::U64 | PrimValKind::I64 => z3::Ast::bv_from_u64(&ctx, v as u64, 64),
+
+                    PrimValKind::Char => z3::Ast::bv_from_u64(&ctx, v as u64, 32),
+
+                    _ => bug!("no solver encoding for values of kind {:?}", kind),
+                }
+            }
+        }
+    }
+
+    fn mir_binop_to_ast<'a>(
+        &self,
+        _ctx: &'a z3::Context,
+        operator: mir::BinOp,
+        left: z3::Ast<'a>,
+        right: z3::Ast<'a>,
+        kind: PrimValKind)
+        -> z3::Ast<'a>
+    {
+        match (operator, kind) {
+            (mir::BinOp::Eq, _) => left._eq(&right),
+            (mir::BinOp::Ne, _) => left._eq(&right).not(),
+
+            (mir::BinOp::Lt, kind) if kind.is_signed_int() => left.bvslt(&right),
+            (mir::BinOp::Lt, _) => left.bvult(&right),
+
+            (mir::BinOp::Le, kind) if kind.is_signed_int() => left.bvsle(&right),
+            (mir::BinOp::Le, _) => left.bvule(&right),
+
+            (mir::BinOp::Gt, kind) if kind.is_signed_int() => left.bvsgt(&right),
+            (mir::BinOp::Gt, _) => left.bvugt(&right),
+
+            (mir::BinOp::Ge, kind) if kind.is_signed_int() => left.bvsge(&right),
+            (mir::BinOp::Ge, _) => left.bvuge(&right),
+
+            (mir::BinOp::Add, _) => left.bvadd(&right),
+            (mir::BinOp::Sub, _) => left.bvsub(&right),
+
+            (mir::BinOp::BitXor, PrimValKind::Bool) => left.xor(&right),
+            (mir::BinOp::BitXor, _) => left.bvxor(&right),
+
+            (mir::BinOp::BitAnd, PrimValKind::Bool) => left.and(&[&right]),
+            (mir::BinOp::BitAnd, _) => left.bvand(&right),
+
+            (mir::BinOp::BitOr, PrimValKind::Bool) => left.or(&[&right]),
+            (mir::BinOp::BitOr, _) => left.bvor(&right),
+
+            (mir::BinOp::Mul, _) => left.bvmul(&right),
+            (mir::BinOp::Shl, _) => left.bvshl(&right),
+
+            (mir::BinOp::Shr, kind) if kind.is_signed_int() => left.bvashr(&right),
+            (mir::BinOp::Shr, _) => left.bvlshr(&right),
+
+            (mir::BinOp::Div, kind) if kind.is_signed_int() => left.bvsdiv(&right),
+            (mir::BinOp::Div, _) => left.bvudiv(&right),
+
+            (mir::BinOp::Rem, kind) if kind.is_signed_int() => left.bvsrem(&right),
+            (mir::BinOp::Rem, _) => left.bvurem(&right),
+
+            _ => bug!("no solver encoding for {:?}", operator),
+        }
+    }
+
+    fn mir_unop_to_ast<'a>(
+        &self,
+        _ctx: &'a z3::Context,
+        operator: mir::UnOp,
+        val: z3::Ast<'a>,)
+        -> z3::Ast<'a>
+    {
+        match operator {
+            mir::UnOp::Not => val.not(),
+            mir::UnOp::Neg => val.bvneg(),
+        }
+    }
+
+    fn mir_intrinsic_to_ast<'a>(
+        &self,
+        ctx: &'a z3::Context,
+        operator: NumericIntrinsic,
+        val: z3::Ast<'a>,
+        kind: PrimValKind)
+        -> z3::Ast<'a>
+    {
+        match operator {
+            NumericIntrinsic::Ctpop => {
+                // no magic in here, just mask each bit and sum them
+                // this avoids branching (ite)
+                let num_bits = kind.num_bytes() as u32 * 8;
+                let zero = z3::Ast::bv_from_u64(&ctx, 0, num_bits);
+                let one = z3::Ast::bv_from_u64(&ctx, 1, num_bits);
+                (0..num_bits)
+                    .map(|idx| z3::Ast::bv_from_u64(&ctx, idx as u64, num_bits))
+                    .fold(zero, |r, idx| r.bvadd(&val.bvlshr(&idx).bvand(&one)))
+            },
+            NumericIntrinsic::Ctlz => {
+                // from http://aggregate.org/MAGIC/#Leading%20Zero%20Count
+                // ctlz(x) = bits - ones(z) where
+                // z = x | x >> 1 | x >> 2 | x >> 3 | ... | x >> num_bits - 1
+                let num_bits = kind.num_bytes() as u32 * 8;
+                let zero = z3::Ast::bv_from_u64(&ctx, 0, num_bits);
+                let z = (0..num_bits)
+                    .map(|idx| z3::Ast::bv_from_u64(&ctx, idx as u64, num_bits))
+                    .fold(zero, |x, idx| x.bvor(&val.bvlshr(&idx)));
+
+                let ones = self.mir_intrinsic_to_ast(ctx, NumericIntrinsic::Ctpop, z, kind);
+                z3::Ast::bv_from_u64(&ctx, num_bits as u64, num_bits).bvsub(&ones)
+            },
+            NumericIntrinsic::Cttz => {
+                // from http://aggregate.org/MAGIC/#Trailing%20Zero%20Count
+                // cttz(x) = ones((x & (−x)) − 1)
+                let num_bits = kind.num_bytes() as u32 * 8;
+                let one = z3::Ast::bv_from_u64(&ctx, 1, num_bits);
+                let z = val.bvand(&val.bvneg()).bvsub(&one);
+                self.mir_intrinsic_to_ast(ctx, NumericIntrinsic::Ctpop, z, kind)
+            },
+        }
+    }
+}
+
+thread_local! {
+    static SOLVER: RefCell<IncrementalSolver> = RefCell::new(IncrementalSolver::new());
+    static QUERY_CACHE: RefCell<QueryCache> = RefCell::new(QueryCache::new());
+}
+
+/// The total time the solver of this thread has spent deciding whether constraints are
+/// satisfiable.
+pub(crate) fn solver_time() -> Duration {
+    SOLVER.with(|solver| solver.borrow().check_time)
+}
+
+/// A z3 solver that is kept alive between queries.
+///
+/// Sibling paths are clones of a common parent, so their constraint lists share a prefix.
+/// The solver remembers which constraints it currently has asserted, grouped into push/pop
+/// scopes, and on each query only backtracks to the longest common prefix and asserts what
+/// is new. Under depth-first search that is usually just the latest branch condition.
+///
+/// This only works if it is always synced to the constraints of a whole path. Independent
+/// slices of a path, as the query cache uses, differ from one query to the next and would
+/// have it start over almost every time.
+struct IncrementalSolver {
+    ctx: &'static z3::Context,
+    solver: z3::Solver<'static>,
+    /// The constraints currently asserted, in order.
+    asserted: Vec<PathConstraint>,
+    /// For every open scope, the index into `asserted` at which it ends.
+    scope_ends: Vec<usize>,
+    /// The time spent in `check` so far.
+    check_time: Duration,
+}
+
+impl IncrementalSolver {
+    fn new() -> Self {
+        let cfg = z3::Config::new();
+        // The context has to outlive the solver stored next to it. There is one per thread,
+        // living as long as the thread does.
+        let ctx: &'static z3::Context = Box::leak(Box::new(z3::Context::new(&cfg)));
+        IncrementalSolver {
+            ctx: ctx,
+            solver: z3::Solver::new(ctx),
+            asserted: Vec::new(),
+            scope_ends: Vec::new(),
+            check_time: Duration::from_secs(0),
+        }
+    }
+
+    fn check(&mut self) -> bool {
+        let start = Instant::now();
+        let answer = self.solver.check();
+        self.check_time += start.elapsed();
+        answer
+    }
+
+    /// Makes `constraints` exactly the set of asserted constraints.
+    fn sync<'tcx>(&mut self, cc: &ConstraintContext<'tcx>, constraints: &[PathConstraint]) {
+        let common = self.asserted.iter()
+            .zip(constraints.iter())
+            .take_while(|&(a, b)| a == b)
+            .count();
+
+        let mut stale_scopes = 0;
+        while self.scope_ends.last().map_or(false, |&end| end > common) {
+            self.scope_ends.pop();
+            stale_scopes += 1;
+        }
+        if stale_scopes > 0 {
+            self.solver.pop(stale_scopes);
+        }
+        let kept = self.scope_ends.last().cloned().unwrap_or(0);
+        self.asserted.truncate(kept);
+
+        if constraints.len() > kept {
+            self.solver.push();
+            for c in &constraints[kept..] {
+                self.solver.assert(&cc.path_constraint_to_ast(self.ctx, *c));
+                self.asserted.push(*c);
+            }
+            self.scope_ends.push(self.asserted.len());
+        }
+    }
+}
+
+/// Union-find over abstract variable ids, which also keeps the path constraints on the
+/// variables of every set. Only the root of a set has an entry in `sizes` and `constraints`.
+#[derive(Clone, Debug)]
+struct Components {
+    parents: HashMap<u32, u32>,
+    /// The number of variables in every set. Smaller sets are attached to larger ones, which
+    /// keeps `find` logarithmic without path compression.
+    sizes: HashMap<u32, u32>,
+    /// Indices into `ConstraintContext::constraints`, in no particular order.
+    constraints: HashMap<u32, Vec<usize>>,
+}
+
+impl Components {
+    fn new() -> Self {
+        Components {
+            parents: HashMap::new(),
+            sizes: HashMap::new(),
+            constraints: HashMap::new(),
+        }
+    }
+
+    fn find(&self, v: u32) -> u32 {
+        let mut root = v;
+        while let Some(&parent) = self.parents.get(&root) {
+            root = parent;
+        }
+        root
+    }
+
+    /// Merges the sets of `a` and `b`. Returns the root of the merged set.
+    fn union(&mut self, a: u32, b: u32) -> u32 {
+        let a = self.find(a);
+        let b = self.find(b);
+        if a == b {
+            return a;
+        }
+        let size_a = self.sizes.remove(&a).unwrap_or(1);
+        let size_b = self.sizes.remove(&b).unwrap_or(1);
+        let (child, root) = if size_a < size_b { (a, b) } else { (b, a) };
+        self.parents.insert(child, root);
+        self.sizes.insert(root, size_a + size_b);
+        if let Some(moved) = self.constraints.remove(&child) {
+            self.constraints.entry(root).or_insert_with(Vec::new).extend(moved);
+        }
+        root
+    }
+
+    /// Adds the constraint at index `idx`, joining the sets of all of its variables.
+    fn add(&mut self, idx: usize, c: &PathConstraint) {
+        let mut root = None;
+        c.for_each_variable(|v| {
+            let joined = match root {
+                None => self.find(v),
+                Some(first) => self.union(first, v),
+            };
+            root = Some(joined);
+        });
+        // Constraints without variables were already satisfied when they were added.
+        if let Some(root) = root {
+            self.constraints.entry(root).or_insert_with(Vec::new).push(idx);
+        }
+    }
+}
+
+fn bool_var(var: AbstractVariable) -> PrimVal {
+    let mut sbytes = [SByte::Concrete(0); 8];
+    sbytes[0] = SByte::Abstract(var);
+    PrimVal::Abstract(sbytes)
+}
@@ -0,0 +1,25 @@
+//! Writing discovered inputs as a fuzzing corpus.
+//!
+//! The layout is the one libFuzzer and cargo-fuzz use: a flat directory with one file per
+//! input, named by the hex SHA-1 of its contents. A corpus directory written by seer can
+//! therefore be handed to a fuzzer as a seed corpus directly.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use sha1::Sha1;
+
+/// Writes `input` to `dir`, creating the directory if needed. An input that is already in the
+/// corpus is not written again. Returns the path of the file.
+pub(crate) fn add_input(dir: &Path, input: &[u8]) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+
+    let mut hasher = Sha1::new();
+    hasher.update(input);
+    let path = dir.join(hasher.digest().to_string());
+    if !path.exists() {
+        fs::write(&path, input)?;
+    }
+    Ok(path)
+}
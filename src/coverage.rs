@@ -0,0 +1,127 @@
+use std::collections::{HashMap, VecDeque};
+
+use rustc::mir;
+use rustc::ty;
+use rustc_data_structures::indexed_vec::Idx;
+
+use eval_context::EvalContext;
+
+/// Records which MIR basic blocks have been executed, across all paths of an exploration.
+pub(crate) struct Coverage<'tcx> {
+    functions: HashMap<ty::Instance<'tcx>, FunctionBlocks>,
+}
+
+struct FunctionBlocks {
+    covered: Vec<bool>,
+    /// The predecessors of every block, to search backwards from the uncovered blocks.
+    predecessors: Vec<Vec<mir::BasicBlock>>,
+    /// For every block, the result of `Coverage::distance_to_uncovered`. Only changes when a
+    /// block of this function is covered for the first time.
+    distances: Vec<Option<u32>>,
+}
+
+impl FunctionBlocks {
+    fn new(mir: &mir::Mir) -> Self {
+        let len = mir.basic_blocks().len();
+        let mut predecessors = vec![Vec::new(); len];
+        for (bb, data) in mir.basic_blocks().iter_enumerated() {
+            for &succ in data.terminator().successors() {
+                predecessors[succ.index()].push(bb);
+            }
+        }
+        FunctionBlocks {
+            covered: vec![false; len],
+            predecessors,
+            distances: vec![Some(0); len],
+        }
+    }
+
+    /// Recomputes `distances` with a single breadth-first search backwards from every
+    /// uncovered block.
+    fn update_distances(&mut self) {
+        let mut queue = VecDeque::new();
+        for (idx, distance) in self.distances.iter_mut().enumerate() {
+            if self.covered[idx] {
+                *distance = None;
+            } else {
+                *distance = Some(0);
+                queue.push_back(idx);
+            }
+        }
+        while let Some(idx) = queue.pop_front() {
+            let distance = self.distances[idx].expect("queued blocks have a distance") + 1;
+            for &pred in &self.predecessors[idx] {
+                if self.distances[pred.index()].is_none() {
+                    self.distances[pred.index()] = Some(distance);
+                    queue.push_back(pred.index());
+                }
+            }
+        }
+    }
+}
+
+impl <'tcx> Coverage<'tcx> {
+    pub fn new() -> Self {
+        Coverage {
+            functions: HashMap::new(),
+        }
+    }
+
+    /// Marks the block that `ecx` is about to execute as covered.
+    pub fn record<'a>(&mut self, ecx: &EvalContext<'a, 'tcx>) {
+        let frame = match ecx.stack().last() {
+            Some(frame) => frame,
+            None => return,
+        };
+        let blocks = self.functions.entry(frame.instance)
+            .or_insert_with(|| FunctionBlocks::new(frame.mir));
+        if !blocks.covered[frame.block.index()] {
+            blocks.covered[frame.block.index()] = true;
+            blocks.update_distances();
+        }
+    }
+
+    /// The number of control-flow edges from `block` to the nearest block of the same function
+    /// that has not been executed yet, or `None` if every reachable block is covered.
+    pub fn distance_to_uncovered(
+        &self,
+        instance: ty::Instance<'tcx>,
+        block: mir::BasicBlock,
+    ) -> Option<u32> {
+        match self.functions.get(&instance) {
+            Some(blocks) => blocks.distances[block.index()],
+            None => Some(0),
+        }
+    }
+
+    pub fn report(&self) -> CoverageReport {
+        let mut functions: Vec<FunctionCoverage> = self.functions.iter().map(|(instance, blocks)| {
+            FunctionCoverage {
+                name: instance.to_string(),
+                total_blocks: blocks.covered.len(),
+                uncovered_blocks: blocks.covered.iter()
+                    .enumerate()
+                    .filter(|&(_, &covered)| !covered)
+                    .map(|(idx, _)| idx)
+                    .collect(),
+            }
+        }).collect();
+        functions.sort_by(|a, b| a.name.cmp(&b.name));
+        CoverageReport { functions }
+    }
+}
+
+/// Basic block coverage of every function that was entered on at least one path.
+#[derive(Clone, Debug)]
+pub struct CoverageReport {
+    pub functions: Vec<FunctionCoverage>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FunctionCoverage {
+    /// The monomorphized function, as printed by rustc.
+    pub name: String,
+    pub total_blocks: usize,
+    /// Indices of the MIR basic blocks (`bb0`, `bb1`, ...) that no path executed.
+    pub uncovered_blocks: Vec<usize>,
+}
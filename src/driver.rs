@@ -1,12 +1,15 @@
 use getopts;
+use rustc::hir::def_id::{DefId, LOCAL_CRATE};
 use rustc::session::Session;
 use rustc::middle::cstore::CrateStore;
+use rustc::ty::TyCtxt;
 use rustc_driver::{self, Compilation, CompilerCalls, RustcDefaultCalls};
 use rustc_driver::driver::{CompileState, CompileController};
 use rustc_errors;
 use rustc::session::config::{self, Input, ErrorOutputType};
 use syntax;
 use syntax::ast::{MetaItemKind, NestedMetaItemKind, self};
+use syntax::attr;
 use std::path::PathBuf;
 
 struct SeerCompilerCalls(Box<RustcDefaultCalls>, ::ExecutionConfig);
@@ -68,10 +71,38 @@ fn after_analysis_run_main<'a, 'tcx>(config: ::ExecutionConfig)
 
         let tcx = state.tcx.unwrap();
         let codemap = state.session.codemap();
-        let limits = resource_limits_from_attributes(state);
+        let limits = config.resource_limits(resource_limits_from_attributes(state));
 
-        if let Some((entry_node_id, _, _)) = *state.session.entry_fn.borrow() {
-            let entry_def_id = tcx.hir.local_def_id(entry_node_id);
+        if config.tests {
+            let tests = find_tests(tcx);
+            if tests.is_empty() {
+                state.session.warn("no tests found");
+            }
+            for def_id in tests {
+                let name = tcx.item_path_str(def_id);
+                let mut executor = ::executor::Executor::new(tcx, def_id, limits, config.clone(), codemap);
+                executor.run();
+                let summary = executor.into_test_summary(name);
+                if let Some(ref f) = config.test_consumer {
+                    (&mut *f.borrow_mut())(summary);
+                }
+            }
+            state.session.abort_if_errors();
+            return;
+        }
+
+        let entry_def_id = match config.entry {
+            Some(ref path) => Some(find_entry_fn(tcx, path).unwrap_or_else(|| {
+                state.session.fatal(&format!("no function `{}` found in this crate", path))
+            })),
+            None => state.session.entry_fn.borrow().map(|(node_id, _, _)| tcx.hir.local_def_id(node_id)),
+        };
+
+        if let Some(entry_def_id) = entry_def_id {
+            if tcx.generics_of(entry_def_id).count() > 0 {
+                state.session.fatal(&format!(
+                    "cannot explore generic function `{}`", tcx.item_path_str(entry_def_id)));
+            }
 
             let mut executor = ::executor::Executor::new(tcx, entry_def_id, limits, config.clone(), codemap);
             executor.run();
@@ -83,6 +114,34 @@ fn after_analysis_run_main<'a, 'tcx>(config: ::ExecutionConfig)
     })
 }
 
+/// Looks up a function of the local crate by its path, which may start with the crate name.
+fn find_entry_fn<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, path: &str) -> Option<DefId> {
+    let crate_prefix = format!("{}::", tcx.crate_name(LOCAL_CRATE));
+    let path = if path.starts_with(&crate_prefix) {
+        &path[crate_prefix.len()..]
+    } else {
+        path
+    };
+    tcx.mir_keys(LOCAL_CRATE).iter()
+        .cloned()
+        .find(|&def_id| tcx.item_path_str(def_id) == path)
+}
+
+/// The `#[test]` functions of the local crate that are not `#[ignore]`d, sorted by path.
+/// `#[should_panic]` tests are left out as well, since every panic would count as an error.
+fn find_tests<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>) -> Vec<DefId> {
+    let mut tests: Vec<DefId> = tcx.mir_keys(LOCAL_CRATE).iter()
+        .cloned()
+        .filter(|&def_id| {
+            let attrs = tcx.get_attrs(def_id);
+            attr::contains_name(&attrs, "test") && !attr::contains_name(&attrs, "ignore") &&
+                !attr::contains_name(&attrs, "should_panic")
+        })
+        .collect();
+    tests.sort_by_key(|&def_id| tcx.item_path_str(def_id));
+    tests
+}
+
 fn resource_limits_from_attributes(state: &CompileState) -> ::ResourceLimits {
     let mut limits = ::ResourceLimits::default();
     let krate = state.hir_crate.as_ref().unwrap();
@@ -103,6 +162,7 @@ fn resource_limits_from_attributes(state: &CompileState) -> ::ResourceLimits {
                             "memory_size" => limits.memory_size = extract_int(value) as u64,
                             "step_limit" => limits.step_limit = extract_int(value) as u64,
                             "stack_limit" => limits.stack_limit = extract_int(value) as usize,
+                            "symbolic_alloc_limit" => limits.symbolic_alloc_limit = extract_int(value) as u64,
                             _ => state.session.span_err(item.span, "unknown miri attribute"),
                         }
                     } else {
@@ -3,6 +3,7 @@ use std::fmt;
 use rustc::mir;
 use rustc::ty::{FnSig, Ty, layout};
 use memory::{MemoryPointer, PointerOffset};
+use value::{PrimVal, PrimValKind};
 use syntax::codemap::Span;
 
 #[derive(Clone, Debug)]
@@ -33,6 +34,7 @@ pub enum EvalError<'tcx> {
     Intrinsic(String),
     Overflow(mir::BinOp),
     OverflowNeg,
+    DivisionByZero,
     RemainderByZero,
     InvalidChar(u128),
     OutOfMemory {
@@ -42,6 +44,16 @@ pub enum EvalError<'tcx> {
     },
     ExecutionTimeLimitReached,
     StackFrameLimitReached,
+    SymbolicAllocLimitReached(u64),
+    /// Not an error in the evaluated program: the current step needs `value` to be concrete.
+    /// `step` catches this, forks the path once for each feasible value in `candidates` and
+    /// takes the step again. If `value` can be something else, that path ends in `otherwise`.
+    NeedsConcreteValue {
+        value: PrimVal,
+        kind: PrimValKind,
+        candidates: Vec<u128>,
+        otherwise: Box<EvalError<'tcx>>,
+    },
     AlignmentCheckFailed {
         required: u64,
         has: u64,
@@ -58,11 +70,30 @@ pub enum EvalError<'tcx> {
     HeapAllocZeroBytes,
     HeapAllocNonPowerOfTwoAlignment(u64),
     Unreachable,
-    Panic,
+    /// The evaluated program panicked. The message and location are only known if they do not
+    /// depend on the input.
+    Panic {
+        message: Option<String>,
+        location: Option<PanicLocation>,
+    },
     ReadFromReturnPointer,
     TypeckError,
 }
 
+/// Where a panic was raised, as passed to the panic machinery by `panic!`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PanicLocation {
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl fmt::Display for PanicLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
 pub type EvalResult<'tcx, T = ()> = Result<T, EvalError<'tcx>>;
 
 impl<'tcx> Error for EvalError<'tcx> {
@@ -112,6 +143,7 @@ impl<'tcx> Error for EvalError<'tcx> {
             EvalError::Overflow(mir::BinOp::Shl) => "attempt to shift left with overflow",
             EvalError::Overflow(op) => panic!("cannot overflow {:?}", op),
             EvalError::OverflowNeg => "attempt to negate with overflow",
+            EvalError::DivisionByZero => "attempt to divide by zero",
             EvalError::RemainderByZero => "attempt to calculate the remainder with a divisor of zero",
             EvalError::NoMirFor(..) =>
                 "mir not found",
@@ -123,6 +155,10 @@ impl<'tcx> Error for EvalError<'tcx> {
                 "reached the configured maximum execution time",
             EvalError::StackFrameLimitReached =>
                 "reached the configured maximum number of stack frames",
+            EvalError::SymbolicAllocLimitReached(_) =>
+                "tried to allocate more memory than the configured maximum for an input-dependent size",
+            EvalError::NeedsConcreteValue { .. } =>
+                "an abstract value had to be made concrete",
             EvalError::AlignmentCheckFailed{..} =>
                 "tried to execute a misaligned read or write",
             EvalError::CalledClosureAsFunction =>
@@ -151,7 +187,7 @@ impl<'tcx> Error for EvalError<'tcx> {
                 "tried to re-, de-, or allocate heap memory with alignment that is not a power of two",
             EvalError::Unreachable =>
                 "entered unreachable code",
-            EvalError::Panic =>
+            EvalError::Panic { .. } =>
                 "the evaluated program panicked",
             EvalError::ReadFromReturnPointer =>
                 "tried to read from the return pointer",
@@ -173,7 +209,11 @@ impl<'tcx> fmt::Display for EvalError<'tcx> {
                                if access { "memory access" } else { "pointer computed" },
                                ptr_offset, ptr.alloc_id, allocation_size)
                     }
-                    _ => unimplemented!(),
+                    PointerOffset::Abstract(_) => {
+                        write!(f, "{} at an input-dependent offset, outside bounds of allocation {} which has size {}",
+                               if access { "memory access" } else { "pointer computed" },
+                               ptr.alloc_id, allocation_size)
+                    }
                 }
             },
             EvalError::NoMirFor(ref func) => write!(f, "no mir for `{}`", func),
@@ -186,6 +226,9 @@ impl<'tcx> fmt::Display for EvalError<'tcx> {
             EvalError::OutOfMemory { allocation_size, memory_size, memory_usage } =>
                 write!(f, "tried to allocate {} more bytes, but only {} bytes are free of the {} byte memory",
                        allocation_size, memory_size - memory_usage, memory_size),
+            EvalError::SymbolicAllocLimitReached(limit) =>
+                write!(f, "tried to allocate an input-dependent number of bytes that can exceed the limit of {}",
+                       limit),
             EvalError::AlignmentCheckFailed { required, has } =>
                write!(f, "tried to access memory with alignment {}, but alignment {} is required",
                       has, required),
@@ -193,6 +236,16 @@ impl<'tcx> fmt::Display for EvalError<'tcx> {
                 write!(f, "expected primitive type, got {}", ty),
             EvalError::Layout(ref err) =>
                 write!(f, "rustc layout computation failed: {:?}", err),
+            EvalError::Panic { ref message, ref location } => {
+                write!(f, "the evaluated program panicked")?;
+                if let Some(ref message) = *message {
+                    write!(f, " at '{}'", message)?;
+                }
+                if let Some(ref location) = *location {
+                    write!(f, ", {}", location)?;
+                }
+                Ok(())
+            }
             _ => write!(f, "{}", self.description()),
         }
     }
@@ -226,6 +279,7 @@ pub enum StaticEvalError {
     Intrinsic(String),
     Overflow(mir::BinOp),
     OverflowNeg,
+    DivisionByZero,
     RemainderByZero,
     InvalidChar(u128),
     OutOfMemory {
@@ -235,6 +289,7 @@ pub enum StaticEvalError {
     },
     ExecutionTimeLimitReached,
     StackFrameLimitReached,
+    SymbolicAllocLimitReached(u64),
     AlignmentCheckFailed {
         required: u64,
         has: u64,
@@ -251,7 +306,10 @@ pub enum StaticEvalError {
     HeapAllocZeroBytes,
     HeapAllocNonPowerOfTwoAlignment(u64),
     Unreachable,
-    Panic,
+    Panic {
+        message: Option<String>,
+        location: Option<PanicLocation>,
+    },
     ReadFromReturnPointer,
     TypeckError,
 }
@@ -299,6 +357,8 @@ impl <'tcx> From<EvalError<'tcx>> for StaticEvalError {
                 StaticEvalError::Overflow(op),
             EvalError::OverflowNeg =>
                 StaticEvalError::OverflowNeg,
+            EvalError::DivisionByZero =>
+                StaticEvalError::DivisionByZero,
             EvalError::RemainderByZero =>
                 StaticEvalError::RemainderByZero,
             EvalError::NoMirFor(ref s) =>
@@ -311,6 +371,14 @@ impl <'tcx> From<EvalError<'tcx>> for StaticEvalError {
                 StaticEvalError::ExecutionTimeLimitReached,
             EvalError::StackFrameLimitReached =>
                 StaticEvalError::StackFrameLimitReached,
+            EvalError::SymbolicAllocLimitReached(limit) =>
+                StaticEvalError::SymbolicAllocLimitReached(limit),
+            // Forks the path where `step` sees it, so it only gets here from a place that
+            // cannot fork, such as the evaluation of a constant.
+            EvalError::NeedsConcreteValue { .. } =>
+                StaticEvalError::Unimplemented(
+                    "an abstract value had to be made concrete outside of a statement or \
+                     terminator".to_string()),
             EvalError::AlignmentCheckFailed { required, has, } =>
                 StaticEvalError::AlignmentCheckFailed { required, has, },
             EvalError::CalledClosureAsFunction =>
@@ -339,8 +407,8 @@ impl <'tcx> From<EvalError<'tcx>> for StaticEvalError {
                 StaticEvalError::HeapAllocNonPowerOfTwoAlignment(n),
             EvalError::Unreachable =>
                 StaticEvalError::Unreachable,
-            EvalError::Panic =>
-                StaticEvalError::Panic,
+            EvalError::Panic { message, location } =>
+                StaticEvalError::Panic { message, location },
             EvalError::ReadFromReturnPointer =>
                 StaticEvalError::ReadFromReturnPointer,
             EvalError::TypeckError =>
@@ -348,3 +416,14 @@ impl <'tcx> From<EvalError<'tcx>> for StaticEvalError {
         }
     }
 }
+
+impl StaticEvalError {
+    /// Whether the path stopped at an operation that seer cannot execute yet, rather than at
+    /// an error in the program under test.
+    pub fn is_engine_limitation(&self) -> bool {
+        match *self {
+            StaticEvalError::Unimplemented(_) => true,
+            _ => false,
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Write;
 
 use rustc::hir::def_id::DefId;
@@ -12,7 +12,7 @@ use syntax::codemap::{self, DUMMY_SP};
 
 use error::{EvalError, EvalResult};
 use place::{Global, GlobalId, Place, PlaceExtra};
-use memory::{Memory, MemoryPointer};
+use memory::{Memory, MemoryPointer, SByte};
 use value::{PrimVal, PrimValKind, Value};
 
 
@@ -37,10 +37,35 @@ pub struct EvalContext<'a, 'tcx: 'a> {
     /// Remove once halting problem is solved.
     pub(crate) steps_remaining: u64,
 
-    /// Environment variables set by `setenv`
+    /// The largest input-dependent allocation size that is explored. Larger sizes are reported
+    /// as errors.
+    pub(crate) symbolic_alloc_limit: u64,
+
+    /// Environment variables set by `setenv`, or symbolic ones that `getenv` has read
     /// Miri does not expose env vars from the host to the emulated program
     pub(crate) env_vars: HashMap<Vec<u8>, MemoryPointer>,
 
+    /// The command-line arguments that `std::env::args` yields, starting with the program name.
+    pub(crate) program_args: Vec<SymbolicString>,
+
+    /// The environment variables with symbolic values, see `terminator::env`.
+    pub(crate) symbolic_env_vars: Vec<SymbolicEnvVar>,
+
+    /// The maximum length of the values of symbolic environment variables.
+    pub(crate) symbolic_env_var_len: u64,
+
+    /// The files of the virtual file system, see `terminator::fs`.
+    pub(crate) files: Vec<VirtualFile>,
+
+    /// The files the program has opened, by file descriptor.
+    pub(crate) open_files: BTreeMap<i32, OpenFile>,
+
+    /// Where the C functions the program calls store `errno`, once one of them has.
+    pub(crate) errno: Option<MemoryPointer>,
+
+    /// How far the program has read stdin, see `terminator::stdin`.
+    pub(crate) stdin: StdinState,
+
     /// CodeMap allows us to look up the source behind a Span
     pub(crate) codemap: &'a codemap::CodeMap,
 }
@@ -54,7 +79,15 @@ impl <'a, 'tcx: 'a> Clone for EvalContext<'a, 'tcx> {
             stack: self.stack.clone(),
             stack_limit: self.stack_limit,
             steps_remaining: self.steps_remaining,
+            symbolic_alloc_limit: self.symbolic_alloc_limit,
             env_vars: self.env_vars.clone(),
+            program_args: self.program_args.clone(),
+            symbolic_env_vars: self.symbolic_env_vars.clone(),
+            symbolic_env_var_len: self.symbolic_env_var_len,
+            files: self.files.clone(),
+            open_files: self.open_files.clone(),
+            errno: self.errno,
+            stdin: self.stdin.clone(),
             codemap: self.codemap,
         }
     }
@@ -139,6 +172,7 @@ pub struct ResourceLimits {
     pub memory_size: u64,
     pub step_limit: u64,
     pub stack_limit: usize,
+    pub symbolic_alloc_limit: u64,
 }
 
 impl Default for ResourceLimits {
@@ -147,10 +181,68 @@ impl Default for ResourceLimits {
             memory_size: 100 * 1024 * 1024, // 100 MB
             step_limit: 1_000_000,
             stack_limit: 100,
+            symbolic_alloc_limit: 64,
         }
     }
 }
 
+/// Bytes from the environment of the evaluated program, like a command-line argument or the
+/// contents of a file.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SymbolicString {
+    /// The bytes of the string, and possibly some after its end.
+    pub(crate) bytes: Vec<SByte>,
+    /// The length of the string, which may depend on the input.
+    pub(crate) len: PrimVal,
+}
+
+/// An environment variable of the evaluated program that may or may not be set.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SymbolicEnvVar {
+    pub(crate) name: Vec<u8>,
+    /// 1 if the variable is set, 0 otherwise.
+    pub(crate) present: PrimVal,
+    pub(crate) value: SymbolicString,
+}
+
+/// A file of the virtual file system.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct VirtualFile {
+    pub(crate) path: Vec<u8>,
+    pub(crate) contents: SymbolicString,
+}
+
+/// A file the program has opened.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct OpenFile {
+    /// The index of the file in `EvalContext::files`.
+    pub(crate) file: usize,
+    /// The offset of the next read.
+    pub(crate) pos: u64,
+}
+
+/// The position of the program in stdin, and where stdin ends.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct StdinState {
+    /// The number of bytes the program has read.
+    pub(crate) read: u64,
+    /// The bytes after those that a read has looked at without consuming them yet, which only
+    /// happens while it forks.
+    pub(crate) pending: Vec<SByte>,
+    /// The length of stdin, once a read has depended on it.
+    pub(crate) len: Option<PrimVal>,
+    /// How many more bytes than it has read the program can find on stdin when it first looks
+    /// for its end.
+    pub(crate) max_remaining: u64,
+    /// Whether `Read::read` may read fewer bytes than asked for, or fail.
+    pub(crate) short_reads: bool,
+    /// How the current read ends, while it forks over the ways it can.
+    pub(crate) read_outcome: Option<PrimVal>,
+    /// Whether a read has failed on this path. No other read fails after it, so that programs
+    /// which retry do not fork forever.
+    pub(crate) failed_read: bool,
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct TyAndPacked<'tcx> {
     pub ty: Ty<'tcx>,
@@ -229,7 +321,15 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
             stack: Vec::new(),
             stack_limit: limits.stack_limit,
             steps_remaining: limits.step_limit,
+            symbolic_alloc_limit: limits.symbolic_alloc_limit,
             env_vars: HashMap::new(),
+            program_args: Vec::new(),
+            symbolic_env_vars: Vec::new(),
+            symbolic_env_var_len: 0,
+            files: Vec::new(),
+            open_files: BTreeMap::new(),
+            errno: None,
+            stdin: StdinState::default(),
             codemap: codemap,
         }
     }
@@ -307,7 +407,8 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                 self.globals.get(&cid).expect("static/const not cached").value
             }
             ConstValue::ByRef(..) => {
-                unimplemented!()
+                return Err(EvalError::Unimplemented(
+                    "constants that are stored by reference".to_string()));
             }
             ConstValue::Scalar(prim_val) => {
                 Value::ByVal(self.rustc_primval_to_primval(prim_val)?)
@@ -564,7 +665,7 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
             Len(ref place) => {
                 let src = self.eval_place(place)?;
                 let ty = self.place_ty(place);
-                let (_, len) = src.elem_ty_and_len(ty, self.tcx);
+                let (_, len) = src.elem_ty_and_len(ty, self.tcx)?;
                 self.write_primval(dest, PrimVal::from_u128(len as u128), dest_ty)?;
             }
 
@@ -585,8 +686,8 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
 
             NullaryOp(mir::NullOp::Box, ty) => {
                 if self.const_env() {
-                    unimplemented!();
-                    //return Err(EvalError::NeedsRfc("\"heap\" allocations".to_string()));
+                    return Err(EvalError::Unimplemented(
+                        "\"heap\" allocations in constants".to_string()));
                 }
                 // FIXME: call the `exchange_malloc` lang item if available
                 if self.type_size(ty)?.expect("box only works with sized types") == 0 {
@@ -600,8 +701,8 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
 
             NullaryOp(mir::NullOp::SizeOf, ty) => {
                 if self.const_env() {
-                    unimplemented!();
-                    //return Err(EvalError::NeedsRfc("computing the size of types (size_of)".to_string()));
+                    return Err(EvalError::Unimplemented(
+                        "computing the size of types (size_of) in constants".to_string()));
                 }
                 let size = self.type_size(ty)?.expect("SizeOf nullary MIR operator called for unsized type");
                 self.write_primval(dest, PrimVal::from_u128(size as u128), dest_ty)?;
@@ -1257,7 +1358,7 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
         use syntax::ast::FloatTy;
 
         if !ptr.has_concrete_offset() {
-            return Ok(None);
+            return self.try_read_value_at_abstract_offset(ptr, ty);
         }
 
         let val = match ty.sty {
@@ -1355,6 +1456,51 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
         Ok(Some(Value::ByVal(val)))
     }
 
+    /// Integers read through an abstract offset become array constraints. Thin pointers, and
+    /// integers read from an allocation holding pointers, are read as one of the table's
+    /// entries. Fat pointers need the offset to be resolved. Other values are left to `copy`.
+    fn try_read_value_at_abstract_offset(
+        &mut self,
+        ptr: MemoryPointer,
+        ty: Ty<'tcx>,
+    ) -> EvalResult<'tcx, Option<Value>> {
+        let holds_pointers = !self.memory.get(ptr.alloc_id)?.relocations.is_empty();
+        let is_pointer = match ty.sty {
+            ty::TyFnPtr(_) | ty::TyRef(..) | ty::TyRawPtr(_) => true,
+            ty::TyAdt(def, _) => def.is_box(),
+            _ => false,
+        };
+        let int_size = match ty.sty {
+            ty::TyBool | ty::TyChar | ty::TyInt(_) | ty::TyUint(_) => self.type_size(ty)?,
+            _ => None,
+        };
+
+        let pointer_size = self.memory.pointer_size();
+        match int_size {
+            Some(size) if size <= 8 && !holds_pointers => {
+                let val = self.memory.read_at_abstract_offset(ptr, size)?;
+                Ok(Some(Value::ByVal(val)))
+            }
+            Some(size) if size <= 8 => {
+                let val = self.memory.read_table_entry(ptr, size)?;
+                Ok(Some(Value::ByVal(val)))
+            }
+            _ if is_pointer && self.type_size(ty)? == Some(pointer_size) => {
+                let val = self.memory.read_table_entry(ptr, pointer_size)?;
+                Ok(Some(Value::ByVal(val)))
+            }
+            _ if is_pointer || holds_pointers => {
+                let size = match self.type_size(ty)? {
+                    Some(size) => size,
+                    None => return Ok(None),
+                };
+                let ptr = self.memory.resolve_abstract_offset(ptr, size)?;
+                self.try_read_value(ptr, ty)
+            }
+            _ => Ok(None),
+        }
+    }
+
     pub(super) fn frame(&self) -> &Frame<'tcx> {
         self.stack.last().expect("no call frames exist")
     }
@@ -1,25 +1,52 @@
-use std::collections::VecDeque;
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Write;
+use std::mem;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
-use rustc::hir::def_id::DefId;
+use rustc::hir::def_id::{DefId, LOCAL_CRATE};
 use rustc::hir::map::definitions::DefPathData;
 use rustc::mir;
 use rustc::ty::{self, TyCtxt, Ty};
-use syntax::codemap::{DUMMY_SP, CodeMap};
+use syntax::codemap::{DUMMY_SP, CodeMap, Span};
 
 use constraints::{Constraint, SatisfiedVar};
-use error::{StaticEvalError, EvalError};
+use coverage::{Coverage, CoverageReport};
+use error::{StaticEvalError, EvalError, EvalResult};
+use memory::MemoryPointer;
 use place::{Place};
+use regression::RegressionTests;
 use eval_context::{EvalContext, Frame, ResourceLimits, StackPopCleanup};
 use value::{PrimVal};
 use format_executor::BestEffortFormatter;
+use search::{SearchStrategy, SearchStrategyKind};
 
 pub struct Executor<'a, 'tcx: 'a> {
     tcx: TyCtxt<'a, 'tcx, 'tcx>,
-    queue: VecDeque<EvalContext<'a, 'tcx>>,
+    queue: Box<SearchStrategy<'a, 'tcx> + 'a>,
+    coverage: Rc<RefCell<Coverage<'tcx>>>,
+    /// Whether anything looks at `coverage`: a coverage consumer or the coverage-guided search.
+    record_coverage: bool,
+    /// States waiting at a join point for another state to merge with. Only used if
+    /// `ExecutionConfig::merge_states` is set.
+    parked: Vec<EvalContext<'a, 'tcx>>,
     config: ExecutionConfig,
     formatter: BestEffortFormatter<'a, 'tcx>,
+    /// The number of paths that ran to completion or to an error.
+    paths_explored: usize,
+    /// The failing paths. Only collected when running tests, for their summaries.
+    errors: Vec<ExecutionComplete>,
+    /// Whether some path was cut short by a resource limit or an unsupported operation.
+    limit_reached: bool,
+    /// Whether the consumer or one of the budgets stopped the exploration.
+    stopped: bool,
+    /// The distinct errors found so far. Only collected if `ExecutionConfig::deduplicate_errors`
+    /// is set.
+    findings: Vec<Finding>,
+    /// The index in `findings` of every kind of error and the place it is attributed to.
+    finding_keys: HashMap<(mem::Discriminant<EvalError<'tcx>>, Option<Span>), usize>,
 }
 
 pub struct FinishStep<'tcx> {
@@ -33,12 +60,50 @@ pub enum FinishStepVariant<'tcx> {
         set_place: Option<(Place<'tcx>, PrimVal, Ty<'tcx>)>,
     },
     Error(EvalError<'tcx>),
+    /// Takes the same step again under the new constraints.
+    Retry,
+}
+
+/// What a file of the virtual file system holds, see `ExecutionConfig::file`.
+#[derive(Clone, Debug)]
+pub(crate) enum FileContents {
+    Concrete(Vec<u8>),
+    /// Up to this many symbolic bytes.
+    Symbolic(u64),
 }
 
 #[derive(Clone)]
 pub struct ExecutionConfig {
     consumer: Option<Rc<RefCell<FnMut(ExecutionComplete) -> bool>>>,
     emit_error: bool,
+    search_strategy: SearchStrategyKind,
+    coverage_consumer: Option<Rc<RefCell<FnMut(CoverageReport)>>>,
+    merge_states: bool,
+    pub(crate) entry: Option<String>,
+    entry_slice_len: u64,
+    symbolic_args: usize,
+    symbolic_arg_len: u64,
+    symbolic_env_vars: Vec<String>,
+    symbolic_env_var_len: u64,
+    files: Vec<(String, FileContents)>,
+    stdin_len: u64,
+    short_reads: bool,
+    pub(crate) tests: bool,
+    pub(crate) test_consumer: Option<Rc<RefCell<FnMut(TestSummary)>>>,
+    json_output: Option<Rc<RefCell<Write>>>,
+    corpus_dir: Option<PathBuf>,
+    corpus_only_errors: bool,
+    regression_tests: Option<Rc<RefCell<RegressionTests>>>,
+    deduplicate_errors: bool,
+    findings_consumer: Option<Rc<RefCell<FnMut(Vec<Finding>)>>>,
+    max_paths: Option<usize>,
+    time_limit: Option<Duration>,
+    solver_time_limit: Option<Duration>,
+    max_pending_states: Option<usize>,
+    memory_size: Option<u64>,
+    step_limit: Option<u64>,
+    stack_limit: Option<usize>,
+    symbolic_alloc_limit: Option<u64>,
 }
 
 impl ExecutionConfig {
@@ -46,6 +111,34 @@ impl ExecutionConfig {
         ExecutionConfig {
             consumer: None,
             emit_error: false,
+            search_strategy: SearchStrategyKind::default(),
+            coverage_consumer: None,
+            merge_states: false,
+            entry: None,
+            entry_slice_len: 8,
+            symbolic_args: 0,
+            symbolic_arg_len: 8,
+            symbolic_env_vars: Vec::new(),
+            symbolic_env_var_len: 8,
+            files: Vec::new(),
+            stdin_len: 8,
+            short_reads: false,
+            tests: false,
+            test_consumer: None,
+            json_output: None,
+            corpus_dir: None,
+            corpus_only_errors: false,
+            regression_tests: None,
+            deduplicate_errors: false,
+            findings_consumer: None,
+            max_paths: None,
+            time_limit: None,
+            solver_time_limit: None,
+            max_pending_states: None,
+            memory_size: None,
+            step_limit: None,
+            stack_limit: None,
+            symbolic_alloc_limit: None,
         }
     }
 
@@ -54,6 +147,250 @@ impl ExecutionConfig {
         self
     }
 
+    /// Chooses the order in which pending paths are explored. Defaults to depth-first.
+    pub fn search_strategy<'a>(&'a mut self, strategy: SearchStrategyKind) -> &'a mut Self {
+        self.search_strategy = strategy;
+        self
+    }
+
+    /// Merge paths that reach the same basic block with the same stack, instead of
+    /// exploring them separately. Differing values become if-then-else constraints.
+    pub fn merge_states<'a>(&'a mut self, merge: bool) -> &'a mut Self {
+        self.merge_states = merge;
+        self
+    }
+
+    /// Explores the function at `path`, e.g. `mycrate::parser::parse_header`, instead of
+    /// `main`. Every argument starts out as a fresh symbolic value named after its parameter.
+    pub fn entry<'a>(&'a mut self, path: &str) -> &'a mut Self {
+        self.entry = Some(path.to_string());
+        self
+    }
+
+    /// How many symbolic elements a slice argument of the entry function gets. Defaults to 8.
+    pub fn entry_slice_len<'a>(&'a mut self, len: u64) -> &'a mut Self {
+        self.entry_slice_len = len;
+        self
+    }
+
+    /// Passes `count` symbolic arguments to the program, after its name. `std::env::args`
+    /// returns them as strings of ASCII characters, labelled `argv[1]`, `argv[2]` and so on.
+    /// Defaults to none.
+    pub fn symbolic_args<'a>(&'a mut self, count: usize) -> &'a mut Self {
+        self.symbolic_args = count;
+        self
+    }
+
+    /// The maximum length in bytes of every symbolic argument. Defaults to 8.
+    pub fn symbolic_arg_len<'a>(&'a mut self, max: u64) -> &'a mut Self {
+        self.symbolic_arg_len = max;
+        self
+    }
+
+    /// Gives the environment variable `name` a symbolic value of ASCII characters, or none at
+    /// all. Paths fork on whether it is set when the program reads it, and its value shows up
+    /// in the input labelled `env[name]`, with no `assignments_str` if it was unset. Every other
+    /// variable is unset. Programs can declare such variables themselves with
+    /// `seer_helper::mksym_env`.
+    pub fn symbolic_env_var<'a>(&'a mut self, name: &str) -> &'a mut Self {
+        self.symbolic_env_vars.push(name.to_string());
+        self
+    }
+
+    /// The maximum length in bytes of the value of every symbolic environment variable.
+    /// Defaults to 8.
+    pub fn symbolic_env_var_len<'a>(&'a mut self, max: u64) -> &'a mut Self {
+        self.symbolic_env_var_len = max;
+        self
+    }
+
+    /// Adds a file holding `contents` at `path` to the file system the program sees. Without
+    /// any, the program finds no files at all. Files can only be opened for reading.
+    pub fn file<'a, B>(&'a mut self, path: &str, contents: B) -> &'a mut Self
+        where B: Into<Vec<u8>>
+    {
+        self.files.push((path.to_string(), FileContents::Concrete(contents.into())));
+        self
+    }
+
+    /// Adds a file at `path` that holds up to `max_len` symbolic bytes. They show up in the
+    /// input labelled `file[path]`.
+    pub fn symbolic_file<'a>(&'a mut self, path: &str, max_len: u64) -> &'a mut Self {
+        self.files.push((path.to_string(), FileContents::Symbolic(max_len)));
+        self
+    }
+
+    /// The maximum number of bytes that reads which stop at the end of stdin, like
+    /// `BufRead::read_line` and `Read::read_to_end`, can find there. Paths fork over the
+    /// lengths stdin can have when the program first looks for its end. Defaults to 8.
+    pub fn stdin_len<'a>(&'a mut self, max: u64) -> &'a mut Self {
+        self.stdin_len = max;
+        self
+    }
+
+    /// Lets `Read::read` on stdin read fewer bytes than the buffer holds, find the end of stdin,
+    /// or fail. Paths fork over each of these, and a read can fail with `ErrorKind::Interrupted`
+    /// or `ErrorKind::Other` once on every path. Off by default, when reads always fill the
+    /// buffer.
+    pub fn short_reads<'a>(&'a mut self, short_reads: bool) -> &'a mut Self {
+        self.short_reads = short_reads;
+        self
+    }
+
+    /// Explores every `#[test]` function of the crate, one after the other, instead of `main`.
+    /// The crate has to be compiled with `--test`. Tests marked `#[ignore]` are skipped.
+    pub fn tests<'a>(&'a mut self, tests: bool) -> &'a mut Self {
+        self.tests = tests;
+        self
+    }
+
+    /// Called after each test has been explored, when running tests.
+    pub fn test_consumer<'a, F>(
+        &'a mut self, consumer: F)
+        -> &'a mut Self
+        where F: FnMut(TestSummary) + 'static
+    {
+        self.test_consumer = Some(Rc::new(RefCell::new(consumer)));
+        self
+    }
+
+    /// Called once exploration has stopped, with the basic blocks that were never executed.
+    pub fn coverage_consumer<'a, F>(
+        &'a mut self, consumer: F)
+        -> &'a mut Self
+        where F: FnMut(CoverageReport) + 'static
+    {
+        self.coverage_consumer = Some(Rc::new(RefCell::new(consumer)));
+        self
+    }
+
+    /// Writes every finished path to `writer` as a line of JSON, see the `json` module for the
+    /// format. This happens in addition to calling the consumer.
+    pub fn json_output<'a, W>(&'a mut self, writer: W) -> &'a mut Self
+        where W: Write + 'static
+    {
+        self.json_output = Some(Rc::new(RefCell::new(writer)));
+        self
+    }
+
+    /// Writes the stdin of every finished path to a file in `dir`, in the layout libFuzzer
+    /// and cargo-fuzz use for their corpora. The directory is created if it does not exist.
+    pub fn corpus_dir<'a, P>(&'a mut self, dir: P) -> &'a mut Self
+        where P: Into<PathBuf>
+    {
+        self.corpus_dir = Some(dir.into());
+        self
+    }
+
+    /// Only add the stdin of paths that ended in an error to the corpus.
+    pub fn corpus_only_errors<'a>(&'a mut self, only_errors: bool) -> &'a mut Self {
+        self.corpus_only_errors = only_errors;
+        self
+    }
+
+    /// Writes a `#[test]` for every path that ends in a panic to the file at `path`. The test
+    /// runs `binary`, which has to be built next to the test, on the same input. Values of
+    /// `mksym` variables are passed on through the helper crate. Not supported when exploring
+    /// an entry function or tests, since the binary cannot be run on their inputs, nor with
+    /// files or short reads, since the binary would not see the same ones.
+    pub fn regression_tests<'a, P>(&'a mut self, path: P, binary: &str) -> &'a mut Self
+        where P: Into<PathBuf>
+    {
+        let tests = RegressionTests::new(path.into(), binary.to_string());
+        self.regression_tests = Some(Rc::new(RefCell::new(tests)));
+        self
+    }
+
+    /// Groups the paths that end in the same kind of error at the same place, see `Finding`.
+    /// Only the first path of every group is reported to the consumer, with `emit_error` and as
+    /// a regression test; the others are just counted. JSON output, the corpus and the errors
+    /// of a test still get every path.
+    pub fn deduplicate_errors<'a>(&'a mut self, deduplicate: bool) -> &'a mut Self {
+        self.deduplicate_errors = deduplicate;
+        self
+    }
+
+    /// Called once exploration has stopped, with every distinct error that was found. Only
+    /// called if errors are deduplicated.
+    pub fn findings_consumer<'a, F>(
+        &'a mut self, consumer: F)
+        -> &'a mut Self
+        where F: FnMut(Vec<Finding>) + 'static
+    {
+        self.findings_consumer = Some(Rc::new(RefCell::new(consumer)));
+        self
+    }
+
+    /// Stops exploring once `max` paths have run to completion or to an error. Paths that are
+    /// only counted because of `deduplicate_errors` count as well.
+    pub fn max_paths<'a>(&'a mut self, max: usize) -> &'a mut Self {
+        self.max_paths = Some(max);
+        self
+    }
+
+    /// Stops exploring once `limit` has passed since exploration started. When running tests,
+    /// every test gets the whole budget, as for the other budgets.
+    pub fn time_limit<'a>(&'a mut self, limit: Duration) -> &'a mut Self {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    /// Stops exploring once the solver has spent `limit` checking constraints, over all paths.
+    pub fn solver_time_limit<'a>(&'a mut self, limit: Duration) -> &'a mut Self {
+        self.solver_time_limit = Some(limit);
+        self
+    }
+
+    /// Drops new states once `max` states are waiting to be explored. The paths through the
+    /// dropped states stay unexplored, like those cut short by a resource limit.
+    pub fn max_pending_states<'a>(&'a mut self, max: usize) -> &'a mut Self {
+        self.max_pending_states = Some(max);
+        self
+    }
+
+    /// The number of bytes every path may allocate. Overrides the `memory_size` crate attribute.
+    pub fn memory_size<'a>(&'a mut self, bytes: u64) -> &'a mut Self {
+        self.memory_size = Some(bytes);
+        self
+    }
+
+    /// The number of steps every path may take. Overrides the `step_limit` crate attribute.
+    pub fn step_limit<'a>(&'a mut self, steps: u64) -> &'a mut Self {
+        self.step_limit = Some(steps);
+        self
+    }
+
+    /// The number of frames the stack of every path may hold. Overrides the `stack_limit`
+    /// crate attribute.
+    pub fn stack_limit<'a>(&'a mut self, frames: usize) -> &'a mut Self {
+        self.stack_limit = Some(frames);
+        self
+    }
+
+    /// The number of allocations with a symbolic size every path may make. Overrides the
+    /// `symbolic_alloc_limit` crate attribute.
+    pub fn symbolic_alloc_limit<'a>(&'a mut self, allocs: u64) -> &'a mut Self {
+        self.symbolic_alloc_limit = Some(allocs);
+        self
+    }
+
+    /// Applies the resource limits set here to `limits`, which come from the crate attributes.
+    pub(crate) fn resource_limits(&self, mut limits: ResourceLimits) -> ResourceLimits {
+        if let Some(bytes) = self.memory_size {
+            limits.memory_size = bytes;
+        }
+        if let Some(steps) = self.step_limit {
+            limits.step_limit = steps;
+        }
+        if let Some(frames) = self.stack_limit {
+            limits.stack_limit = frames;
+        }
+        if let Some(allocs) = self.symbolic_alloc_limit {
+            limits.symbolic_alloc_limit = allocs;
+        }
+        limits
+    }
+
     /// The consumer returns `true` if it wants the executor to continue.
     pub fn consumer<'a, F>(
         &'a mut self, consumer: F)
@@ -69,10 +406,63 @@ impl ExecutionConfig {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ExecutionComplete {
     pub input: Vec<SatisfiedVar>,
     pub result: Result<(), StaticEvalError>,
+    /// The call stack where the path ended, innermost frame first. The first frame is the
+    /// statement or terminator that finished the path.
+    pub backtrace: Vec<BacktraceFrame>,
+}
+
+/// A frame of the call stack, with the source location it was executing: the current statement
+/// for the innermost frame, and the call to the next frame for the others.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BacktraceFrame {
+    /// The function, including its generic arguments, e.g. `parser::parse::<u8>`.
+    pub function: String,
+    pub file: String,
+    pub line: usize,
+    /// Starts at 1, like `line`.
+    pub column: usize,
+}
+
+/// A distinct error found while deduplicating errors. Two paths run into the same error if the
+/// variants of their errors match and they happened at the same span of the innermost frame
+/// that belongs to the explored crate. That way panics and other errors raised inside the
+/// standard library are told apart by the code that called into it.
+#[derive(Clone, Debug)]
+pub struct Finding {
+    /// The first path that ran into the error.
+    pub example: ExecutionComplete,
+    /// The frame of `example.backtrace` that the error is attributed to.
+    pub location: Option<BacktraceFrame>,
+    /// How many paths ran into the error, including `example`.
+    pub occurrences: usize,
+}
+
+/// The outcome of exploring a single `#[test]` function.
+#[derive(Clone, Debug)]
+pub struct TestSummary {
+    /// The path of the test function, e.g. `tests::parses_header`.
+    pub name: String,
+    /// The number of paths that ran to completion or to an error.
+    pub paths_explored: usize,
+    /// Every path that ended in an error, with the inputs that led there.
+    pub errors: Vec<ExecutionComplete>,
+    pub status: ExplorationStatus,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExplorationStatus {
+    /// Every feasible path was explored to its end.
+    Finished,
+    /// Some paths were cut short by a resource limit or by an operation seer does not
+    /// support, so parts of the function may be unexplored.
+    LimitReached,
+    /// The consumer asked to stop, or a budget of the exploration like
+    /// `ExecutionConfig::max_paths` ran out, before all paths were explored.
+    Stopped,
 }
 
 impl <'a, 'tcx: 'a> Executor<'a, 'tcx> {
@@ -86,30 +476,49 @@ impl <'a, 'tcx: 'a> Executor<'a, 'tcx> {
         -> Self
     {
 
+        let coverage = Rc::new(RefCell::new(Coverage::new()));
+        let record_coverage = config.coverage_consumer.is_some() ||
+            config.search_strategy == SearchStrategyKind::CoverageGuided;
         let mut result = Executor {
             tcx: tcx,
-            queue: VecDeque::new(),
+            queue: config.search_strategy.build(coverage.clone()),
+            coverage: coverage,
+            record_coverage,
+            parked: Vec::new(),
             config: config,
             formatter: BestEffortFormatter::new(tcx, limits, codemap),
+            paths_explored: 0,
+            errors: Vec::new(),
+            limit_reached: false,
+            stopped: false,
+            findings: Vec::new(),
+            finding_keys: HashMap::new(),
         };
 
         let mut ecx = EvalContext::new(tcx, limits, codemap);
         let instance = ty::Instance::mono(tcx, def_id);
-        let mir = ecx.load_mir(instance.def).expect("main function's MIR not found");
+        let mir = ecx.load_mir(instance.def).expect("entry function's MIR not found");
 
-        if !mir.return_ty().is_nil() || mir.arg_count > 0 {
-            let msg = "seer does not support main functions without `fn()` type signatures";
-            tcx.sess.err(&EvalError::Unimplemented(String::from(msg)).to_string());
-            unimplemented!()
+        if let Err(e) = push_entry_frame(&mut ecx, instance, mir, result.config.entry_slice_len) {
+            tcx.sess.err(&format!("could not call {}: {}", instance, e));
+            // Leave the queue empty, so that `run` has nothing to explore.
+            return result;
         }
 
-        ecx.push_stack_frame(
-            instance,
-            DUMMY_SP,
-            &mir,
-            Place::undef(),
-            StackPopCleanup::None,
-        ).expect("could not allocate first stack frame");
+        let program_name = tcx.crate_name(LOCAL_CRATE).as_str();
+        let (count, max_len) = (result.config.symbolic_args, result.config.symbolic_arg_len);
+        if let Err(e) = ecx.init_program_args(&program_name, count, max_len) {
+            tcx.sess.err(&format!("could not set up the program arguments: {}", e));
+            return result;
+        }
+        let max_len = result.config.symbolic_env_var_len;
+        if let Err(e) = ecx.init_symbolic_env_vars(&result.config.symbolic_env_vars, max_len) {
+            tcx.sess.err(&format!("could not set up the environment variables: {}", e));
+            return result;
+        }
+        ecx.init_files(&result.config.files);
+        ecx.stdin.max_remaining = result.config.stdin_len;
+        ecx.stdin.short_reads = result.config.short_reads;
 
         result.push_eval_context(ecx);
 
@@ -117,84 +526,219 @@ impl <'a, 'tcx: 'a> Executor<'a, 'tcx> {
     }
 
     pub fn push_eval_context(&mut self, ecx: EvalContext<'a, 'tcx>) {
-        // Push onto the front so that we go depth-first.
-        // Pushing onto the back consumes more memory and may be less
-        // cache-friendly.
-        self.queue.push_front(ecx);
+        if let Some(max) = self.config.max_pending_states {
+            if self.queue.len() + self.parked.len() >= max {
+                self.limit_reached = true;
+                return;
+            }
+        }
+        self.queue.push(ecx);
     }
 
     fn pop_eval_context(&mut self) -> Option<EvalContext<'a, 'tcx>> {
-        self.queue.pop_front()
+        self.queue.pop()
+    }
+
+    /// Picks the next state to step. When merging, states that arrive at a join point wait
+    /// there until every other pending state has either joined them or also stopped at a
+    /// join point. The most recently parked state, usually the innermost join, goes first.
+    fn next_eval_context(&mut self) -> Option<EvalContext<'a, 'tcx>> {
+        if !self.config.merge_states {
+            return self.pop_eval_context();
+        }
+
+        while let Some(ecx) = self.pop_eval_context() {
+            if !ecx.at_join_point() {
+                return Some(ecx);
+            }
+            self.park(ecx);
+        }
+        self.parked.pop()
+    }
+
+    fn park(&mut self, ecx: EvalContext<'a, 'tcx>) {
+        for idx in (0..self.parked.len()).rev() {
+            if self.parked[idx].try_merge(&ecx) {
+                let merged = self.parked.remove(idx);
+                self.parked.push(merged);
+                return;
+            }
+        }
+        self.parked.push(ecx);
     }
 
     // return true if we should continue with other executions
-    fn report_error(&mut self, ecx: EvalContext<'a, 'tcx>, e: EvalError) -> bool {
-        if self.config.emit_error {
+    fn report_error(&mut self, ecx: EvalContext<'a, 'tcx>, e: EvalError<'tcx>) -> bool {
+        // An unsupported operation ends only the path that ran into it. Say where it was, since
+        // the consumer may well go on with the others.
+        let e = match e {
+            EvalError::OutOfMemory { .. } |
+            EvalError::ExecutionTimeLimitReached |
+            EvalError::StackFrameLimitReached |
+            EvalError::SymbolicAllocLimitReached(_) |
+            EvalError::NeedsConcreteValue { .. } => {
+                self.limit_reached = true;
+                e
+            }
+            EvalError::Unimplemented(msg) => {
+                let msg = match current_span(&ecx) {
+                    Some(span) => format!("{} at {}", msg, ecx.codemap.span_to_string(span)),
+                    None => msg,
+                };
+                self.limit_reached = true;
+                EvalError::Unimplemented(msg)
+            }
+            e => e,
+        };
+
+        let (key, duplicate) = if self.config.deduplicate_errors {
+            let key = (mem::discriminant(&e), error_site(&ecx).map(|(_, span)| span));
+            match self.finding_keys.get(&key) {
+                Some(&idx) => {
+                    self.findings[idx].occurrences += 1;
+                    (None, true)
+                }
+                None => (Some(key), false),
+            }
+        } else {
+            (None, false)
+        };
+
+        if self.config.emit_error && !duplicate {
             report(self.tcx, &ecx, e.clone());
         }
 
-        match self.config.consumer {
-            Some(ref f) => {
-                (&mut *f.borrow_mut())(ExecutionComplete {
-                    input: ecx.memory.constraints.get_satisfying_values(&self.formatter),
-                    result: Err(e.into())
-                })
+        let go_on = self.complete_path(&ecx, Err(e.into()), duplicate);
+        if let Some(key) = key {
+            // `complete_path` added the finding.
+            self.finding_keys.insert(key, self.findings.len() - 1);
+        }
+        go_on
+    }
+
+    /// Hands a finished path to the consumer. Returns whether to continue with other paths.
+    ///
+    /// A `duplicate` error was already found on another path. It still goes to the JSON output,
+    /// the corpus and the errors of a test, but not to the consumer or the regression tests.
+    fn complete_path(
+        &mut self,
+        ecx: &EvalContext<'a, 'tcx>,
+        result: Result<(), StaticEvalError>,
+        duplicate: bool,
+    ) -> bool {
+        self.paths_explored += 1;
+        let consume = self.config.consumer.is_some() && !duplicate;
+        let collect_error = self.config.tests && result.is_err();
+        let add_to_corpus = self.config.corpus_dir.is_some() &&
+            (result.is_err() || !self.config.corpus_only_errors);
+        let add_regression_test = self.config.regression_tests.is_some() && result.is_err() &&
+            self.config.entry.is_none() && !self.config.tests && !self.config.short_reads &&
+            self.config.files.is_empty() && !duplicate;
+        let add_finding = self.config.deduplicate_errors && result.is_err() && !duplicate;
+        if !consume && self.config.json_output.is_none() &&
+            !collect_error && !add_to_corpus && !add_regression_test && !add_finding
+        {
+            return true;
+        }
+
+        let complete = ExecutionComplete {
+            input: ecx.memory.constraints.get_satisfying_values(&self.formatter),
+            result,
+            backtrace: backtrace(ecx),
+        };
+        if let Some(ref out) = self.config.json_output {
+            let line = ::json::path_to_json(&complete, ecx.codemap);
+            if let Err(e) = writeln!(out.borrow_mut(), "{}", line) {
+                self.tcx.sess.err(&format!("could not write JSON output: {}", e));
+            }
+        }
+        if add_to_corpus {
+            let dir = self.config.corpus_dir.as_ref().expect("checked above");
+            let stdin = complete.input.iter().find(|var| var.label == "stdin");
+            if let Some(stdin) = stdin {
+                if let Err(e) = ::corpus::add_input(dir, &stdin.assignments) {
+                    self.tcx.sess.err(&format!(
+                        "could not write to corpus directory {}: {}", dir.display(), e));
+                }
+            }
+        }
+        if add_regression_test {
+            let tests = self.config.regression_tests.as_ref().expect("checked above");
+            if let Err(e) = tests.borrow_mut().add(&complete) {
+                self.tcx.sess.err(&format!("could not write regression tests: {}", e));
             }
-            None => true,
+        }
+        if collect_error {
+            self.errors.push(complete.clone());
+        }
+        if add_finding {
+            self.findings.push(Finding {
+                example: complete.clone(),
+                location: error_site(ecx).map(|(instance, span)| {
+                    backtrace_frame(ecx.codemap, instance, span)
+                }),
+                occurrences: 1,
+            });
+        }
+        match self.config.consumer {
+            Some(ref f) if consume => (&mut *f.borrow_mut())(complete),
+            _ => true,
         }
     }
 
     pub fn run(&mut self) {
-        'main_loop: while let Some(mut ecx) = self.pop_eval_context() {
+        let started = Instant::now();
+        let solver_time_before = ::constraints::solver_time();
+        'main_loop: while !self.budget_exhausted(started, solver_time_before) {
+            let mut ecx = match self.next_eval_context() {
+                Some(ecx) => ecx,
+                None => break,
+            };
+            if self.record_coverage {
+                self.coverage.borrow_mut().record(&ecx);
+            }
             match ecx.step() {
                 Ok((true, None)) => {
                     self.push_eval_context(ecx)
                 }
                 Ok((true, Some(branches))) => {
                     if branches.is_empty() {
-                        // no feasible branch. should throw error
-                        unimplemented!()
+                        // No branch is feasible, so no input reaches this state. Drop it.
+                        continue;
                     } else {
                         let iter = ::std::iter::repeat(ecx).zip(branches.into_iter());
                         for (mut cx, finish_step) in iter {
                             let FinishStep {constraints, variant} = finish_step;
-                            let mut no_errors: bool = true;
                             for constraint in constraints {
                                 cx.memory.constraints.push_constraint(constraint);
-                                match variant {
-                                    FinishStepVariant::Continue { goto_block, set_place} => {
-                                        if let Some((place, prim, ty)) = set_place {
-                                            if let Err(_) = cx.write_primval(place, prim, ty) {
-                                                unimplemented!()
+                            }
+                            match variant {
+                                FinishStepVariant::Continue { goto_block, set_place} => {
+                                    if let Some((place, prim, ty)) = set_place {
+                                        if let Err(e) = cx.write_primval(place, prim, ty) {
+                                            if !self.report_error(cx, e) {
+                                                break 'main_loop;
                                             }
+                                            continue;
                                         }
-                                        cx.goto_block(goto_block);
                                     }
-                                    FinishStepVariant::Error(ref e) => {
-                                        if !self.report_error(cx.clone(), e.clone()) {
-                                            break 'main_loop;
-                                        }
-                                        no_errors = false;
+                                    cx.goto_block(goto_block);
+                                }
+                                FinishStepVariant::Error(e) => {
+                                    // only continue along branches without errors
+                                    if !self.report_error(cx, e) {
+                                        break 'main_loop;
                                     }
+                                    continue;
                                 }
+                                FinishStepVariant::Retry => {}
                             }
-                            if no_errors {
-                                // only continue along branches without errors
-                                self.push_eval_context(cx);
-                            }
+                            self.push_eval_context(cx);
                         }
                     }
                 }
                 Ok((false, _)) => {
-                    let go_on = match self.config.consumer {
-                        Some(ref f) => {
-                            (&mut *f.borrow_mut())(ExecutionComplete {
-                                input: ecx.memory.constraints.get_satisfying_values(&self.formatter),
-                                result: Ok(())
-                            })
-                        }
-                        None => true,
-                    };
+                    let go_on = self.complete_path(&ecx, Ok(()), false);
                     let leaks = ecx.memory.leak_report();
                     if leaks != 0 {
                         self.tcx.sess.err("the evaluated program leaked memory");
@@ -210,18 +754,185 @@ impl <'a, 'tcx: 'a> Executor<'a, 'tcx> {
                 }
             }
         }
+        self.stopped = !self.queue.is_empty() || !self.parked.is_empty();
+
+        if let Some(ref f) = self.config.coverage_consumer {
+            (&mut *f.borrow_mut())(self.coverage.borrow().report());
+        }
+        if let Some(ref f) = self.config.findings_consumer {
+            if self.config.deduplicate_errors {
+                (&mut *f.borrow_mut())(self.findings.clone());
+            }
+        }
+    }
+
+    /// Whether one of the budgets of the exploration, which started at `started` when the
+    /// solver had been busy for `solver_time_before`, has run out.
+    fn budget_exhausted(&self, started: Instant, solver_time_before: Duration) -> bool {
+        if let Some(max) = self.config.max_paths {
+            if self.paths_explored >= max {
+                return true;
+            }
+        }
+        if let Some(limit) = self.config.time_limit {
+            if started.elapsed() >= limit {
+                return true;
+            }
+        }
+        if let Some(limit) = self.config.solver_time_limit {
+            if ::constraints::solver_time() - solver_time_before >= limit {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Summarizes the exploration so far, for reporting on the test called `name`.
+    pub(crate) fn into_test_summary(self, name: String) -> TestSummary {
+        let status = if self.stopped {
+            ExplorationStatus::Stopped
+        } else if self.limit_reached {
+            ExplorationStatus::LimitReached
+        } else {
+            ExplorationStatus::Finished
+        };
+        TestSummary {
+            name,
+            paths_explored: self.paths_explored,
+            errors: self.errors,
+            status,
+        }
     }
 }
 
 
-fn report(tcx: TyCtxt, ecx: &EvalContext, e: EvalError) {
-    let frame = ecx.stack().last().expect("stackframe was empty");
-    let block = &frame.mir.basic_blocks()[frame.block];
-    let span = if frame.stmt < block.statements.len() {
-        block.statements[frame.stmt].source_info.span
+/// Pushes the frame of the entry function, with a fresh symbolic value in every argument.
+fn push_entry_frame<'a, 'tcx: 'a>(
+    ecx: &mut EvalContext<'a, 'tcx>,
+    instance: ty::Instance<'tcx>,
+    mir: &'tcx mir::Mir<'tcx>,
+    slice_len: u64,
+) -> EvalResult<'tcx> {
+    let return_ty = mir.return_ty();
+    let return_place = if return_ty.is_nil() {
+        Place::undef()
     } else {
-        block.terminator().source_info.span
+        let ptr = ecx.alloc_ptr_with_substs(return_ty, instance.substs)?;
+        // Belongs to the caller that Seer stands in for, so it outlives the path.
+        ecx.memory.mark_static_initalized(ptr.alloc_id, true)?;
+        Place::from_ptr(ptr)
     };
+    ecx.push_stack_frame(instance, DUMMY_SP, mir, return_place, StackPopCleanup::None)?;
+
+    for (idx, local) in mir.args_iter().enumerate() {
+        let decl = &mir.local_decls[local];
+        let label = match decl.name {
+            Some(name) => name.to_string(),
+            None => format!("arg{}", idx),
+        };
+        let dest = ecx.eval_place(&mir::Place::Local(local))?;
+        let ptr = ecx.force_allocation(dest)?.to_ptr()?;
+        write_symbolic_value(ecx, ptr, decl.ty, label, slice_len)?;
+    }
+    Ok(())
+}
+
+/// Fills the memory at `ptr` with a fresh symbolic value of type `ty`. A reference points to a
+/// fresh symbolic value of its own, labelled `*label`; a slice reference to `slice_len` of them.
+fn write_symbolic_value<'a, 'tcx: 'a>(
+    ecx: &mut EvalContext<'a, 'tcx>,
+    ptr: MemoryPointer,
+    ty: Ty<'tcx>,
+    label: String,
+    slice_len: u64,
+) -> EvalResult<'tcx> {
+    match ty.sty {
+        ty::TyRef(_, pointee, _) => match pointee.sty {
+            ty::TySlice(elem) => {
+                let array_ty = ecx.tcx.mk_array(elem, slice_len);
+                let target = alloc_entry_pointee(ecx, array_ty)?;
+                write_symbolic_value(ecx, target, array_ty, label, slice_len)?;
+                ecx.memory.write_ptr(ptr, target)?;
+                let len_ptr = ptr.offset(ecx.memory.pointer_size(), ecx.memory.layout)?;
+                ecx.memory.write_usize(len_ptr, slice_len)
+            }
+            _ if ecx.type_is_sized(pointee) => {
+                let target = alloc_entry_pointee(ecx, pointee)?;
+                write_symbolic_value(ecx, target, pointee, format!("*{}", label), slice_len)?;
+                ecx.memory.write_ptr(ptr, target)
+            }
+            _ => Err(EvalError::Unimplemented(format!("symbolic arguments of type {}", ty))),
+        },
+        _ => {
+            let size = ecx.type_size(ty)?.expect("arguments must be sized");
+            ecx.memory.write_fresh_symbolic_var(ptr, size, label, ty)
+        }
+    }
+}
+
+/// Allocates the value behind a reference argument of the entry function. Like the return
+/// place, it belongs to the caller and is never freed on the path.
+fn alloc_entry_pointee<'a, 'tcx: 'a>(
+    ecx: &mut EvalContext<'a, 'tcx>,
+    ty: Ty<'tcx>,
+) -> EvalResult<'tcx, MemoryPointer> {
+    let ptr = ecx.alloc_ptr(ty)?;
+    ecx.memory.mark_static_initalized(ptr.alloc_id, true)?;
+    Ok(ptr)
+}
+
+/// The span of the statement or terminator that `ecx` is about to execute.
+fn current_span(ecx: &EvalContext) -> Option<Span> {
+    let frame = ecx.stack().last()?;
+    let block = &frame.mir.basic_blocks()[frame.block];
+    if frame.stmt < block.statements.len() {
+        Some(block.statements[frame.stmt].source_info.span)
+    } else {
+        Some(block.terminator().source_info.span)
+    }
+}
+
+/// The instance of every frame of `ecx`, innermost first, and the span it is executing: the
+/// current statement for the innermost frame, and the call to the next frame for the others.
+fn frame_spans<'a, 'tcx>(ecx: &EvalContext<'a, 'tcx>) -> Vec<(ty::Instance<'tcx>, Span)> {
+    let mut frames = Vec::new();
+    let mut span = current_span(ecx);
+    for frame in ecx.stack().iter().rev() {
+        if let Some(span) = span {
+            frames.push((frame.instance, span));
+        }
+        span = Some(frame.span);
+    }
+    frames
+}
+
+/// The frames of the call stack of `ecx`, innermost first.
+fn backtrace(ecx: &EvalContext) -> Vec<BacktraceFrame> {
+    frame_spans(ecx).into_iter()
+        .map(|(instance, span)| backtrace_frame(ecx.codemap, instance, span))
+        .collect()
+}
+
+fn backtrace_frame(codemap: &CodeMap, instance: ty::Instance, span: Span) -> BacktraceFrame {
+    let loc = codemap.lookup_char_pos(span.lo());
+    BacktraceFrame {
+        function: instance.to_string(),
+        file: loc.file.name.to_string(),
+        line: loc.line,
+        column: loc.col.0 + 1,
+    }
+}
+
+/// The frame that an error in `ecx` is attributed to: the innermost one that belongs to the
+/// explored crate, or the innermost one if none does.
+fn error_site<'a, 'tcx>(ecx: &EvalContext<'a, 'tcx>) -> Option<(ty::Instance<'tcx>, Span)> {
+    let frames = frame_spans(ecx);
+    let local = frames.iter().find(|&&(instance, _)| instance.def_id().is_local()).cloned();
+    local.or_else(|| frames.first().cloned())
+}
+
+fn report(tcx: TyCtxt, ecx: &EvalContext, e: EvalError) {
+    let span = current_span(ecx).expect("stackframe was empty");
     let mut err = tcx.sess.struct_span_err(span, &e.to_string());
     for &Frame { instance, span, .. } in ecx.stack().iter().rev() {
         if tcx.def_key(instance.def_id()).disambiguated_data.data == DefPathData::ClosureExpr {
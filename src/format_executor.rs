@@ -67,7 +67,7 @@ pub struct FormatExecutor<'a, 'tcx: 'a> {
 
 /// find field by name, return Ty and offset
 /// panics if ty is not of the TyAdt variant
-fn field_ty_and_offset<'a, 'tcx: 'a>(ecx: &EvalContext<'a, 'tcx>, ty: Ty<'tcx>, field_substs: &ty::subst::Substs<'tcx>, name: &str) -> Option<(Ty<'tcx>, u64)> {
+pub(crate) fn field_ty_and_offset<'a, 'tcx: 'a>(ecx: &EvalContext<'a, 'tcx>, ty: Ty<'tcx>, field_substs: &ty::subst::Substs<'tcx>, name: &str) -> Option<(Ty<'tcx>, u64)> {
     for (field_num, field_def) in ty.ty_adt_def().unwrap().all_fields().enumerate() {
         if field_def.ident.name == name {
             let field_ty = field_def.ty(ecx.tcx, field_substs);
@@ -163,6 +163,7 @@ impl<'a, 'tcx: 'a> FormatExecutor<'a, 'tcx> {
                                 FinishStepVariant::Error(ref e) => {
                                     return Err(e.clone());
                                 }
+                                FinishStepVariant::Retry => {}
                             }
                         }
                     }
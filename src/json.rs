@@ -0,0 +1,201 @@
+//! Serialization of finished paths as JSON Lines, so that scripts can consume the results of
+//! an exploration without parsing `Debug` output.
+//!
+//! Every path becomes one object on its own line (wrapped here):
+//!
+//! ```text
+//! {"inputs":[{"label":"stdin","bytes":[43],"assignments_str":null}],
+//!  "result":"error","error":{"variant":"Panic","fields":{"message":"explicit panic",
+//!  "location":{"file":"src/main.rs","line":9,"column":9}}},
+//!  "span":{"file":"src/main.rs","line":9,"column":9},
+//!  "stack":[{"function":"main","file":"src/main.rs","line":9,"column":9}]}
+//! ```
+//!
+//! Struct variants of the error have an object as `fields`, tuple variants an array.
+
+use std::fmt::Write;
+
+use syntax::codemap::{CodeMap, Span};
+
+use error::StaticEvalError;
+use executor::{BacktraceFrame, ExecutionComplete};
+use memory::{MemoryPointer, PointerOffset};
+
+/// Renders a finished path as a single line of JSON, without the trailing newline.
+///
+/// The `span` field is where the path ended, the location of the innermost frame of `stack`.
+pub(crate) fn path_to_json(complete: &ExecutionComplete, codemap: &CodeMap) -> String {
+    let mut out = String::new();
+
+    out.push_str("{\"inputs\":[");
+    for (idx, var) in complete.input.iter().enumerate() {
+        if idx > 0 {
+            out.push(',');
+        }
+        out.push_str("{\"label\":");
+        push_str(&mut out, &var.label);
+        out.push_str(",\"bytes\":");
+        push_bytes(&mut out, &var.assignments);
+        out.push_str(",\"assignments_str\":");
+        match var.assignments_str {
+            Some(ref s) => push_str(&mut out, s),
+            None => out.push_str("null"),
+        }
+        out.push('}');
+    }
+    out.push(']');
+
+    match complete.result {
+        Ok(()) => out.push_str(",\"result\":\"ok\",\"error\":null"),
+        Err(ref e) => {
+            out.push_str(",\"result\":\"error\",\"error\":");
+            push_error(&mut out, e, codemap);
+        }
+    }
+
+    out.push_str(",\"span\":");
+    match complete.backtrace.first() {
+        Some(frame) => push_frame(&mut out, frame, false),
+        None => out.push_str("null"),
+    }
+
+    out.push_str(",\"stack\":[");
+    for (idx, frame) in complete.backtrace.iter().enumerate() {
+        if idx > 0 {
+            out.push(',');
+        }
+        push_frame(&mut out, frame, true);
+    }
+    out.push_str("]}");
+
+    out
+}
+
+fn push_error(out: &mut String, e: &StaticEvalError, codemap: &CodeMap) {
+    use error::StaticEvalError::*;
+
+    // The `Debug` output of a variant starts with its name.
+    let debug = format!("{:?}", e);
+    let variant = debug.split(|c: char| !c.is_alphanumeric()).next().unwrap_or("");
+    out.push_str("{\"variant\":");
+    push_str(out, variant);
+    out.push_str(",\"fields\":");
+    match *e {
+        NoMirFor(ref s) | Unimplemented(ref s) | Intrinsic(ref s) => {
+            out.push('[');
+            push_str(out, s);
+            out.push(']');
+        }
+        UnterminatedCString(ptr) => {
+            out.push('[');
+            push_pointer(out, ptr);
+            out.push(']');
+        }
+        PointerOutOfBounds { ptr, access, allocation_size } => {
+            out.push_str("{\"ptr\":");
+            push_pointer(out, ptr);
+            write!(out, ",\"access\":{},\"allocation_size\":{}}}",
+                   access, allocation_size).unwrap();
+        }
+        InvalidBoolOp(op) | Overflow(op) => {
+            out.push('[');
+            push_str(out, &format!("{:?}", op));
+            out.push(']');
+        }
+        ArrayIndexOutOfBounds(span, len, index) => {
+            out.push('[');
+            push_location(out, span, codemap);
+            write!(out, ",{},{}]", len, index).unwrap();
+        }
+        InvalidChar(c) => write!(out, "[{}]", c).unwrap(),
+        OutOfMemory { allocation_size, memory_size, memory_usage } => {
+            write!(out, "{{\"allocation_size\":{},\"memory_size\":{},\"memory_usage\":{}}}",
+                   allocation_size, memory_size, memory_usage).unwrap();
+        }
+        SymbolicAllocLimitReached(n) | HeapAllocNonPowerOfTwoAlignment(n) => {
+            write!(out, "[{}]", n).unwrap()
+        }
+        AlignmentCheckFailed { required, has } => {
+            write!(out, "{{\"required\":{},\"has\":{}}}", required, has).unwrap();
+        }
+        Panic { ref message, ref location } => {
+            out.push_str("{\"message\":");
+            match *message {
+                Some(ref message) => push_str(out, message),
+                None => out.push_str("null"),
+            }
+            out.push_str(",\"location\":");
+            match *location {
+                Some(ref location) => {
+                    out.push_str("{\"file\":");
+                    push_str(out, &location.file);
+                    write!(out, ",\"line\":{},\"column\":{}}}", location.line, location.column)
+                        .unwrap();
+                }
+                None => out.push_str("null"),
+            }
+            out.push('}');
+        }
+        _ => out.push_str("[]"),
+    }
+    out.push('}');
+}
+
+fn push_pointer(out: &mut String, ptr: MemoryPointer) {
+    write!(out, "{{\"alloc_id\":{},\"offset\":", ptr.alloc_id.0).unwrap();
+    match ptr.offset {
+        PointerOffset::Concrete(n) => write!(out, "{}", n).unwrap(),
+        // Depends on the input, so there is no single number to report.
+        PointerOffset::Abstract(_) => out.push_str("null"),
+    }
+    out.push('}');
+}
+
+/// Writes the start of `span` as an object with 1-based `line` and `column`.
+fn push_location(out: &mut String, span: Span, codemap: &CodeMap) {
+    let loc = codemap.lookup_char_pos(span.lo());
+    out.push_str("{\"file\":");
+    push_str(out, &loc.file.name.to_string());
+    write!(out, ",\"line\":{},\"column\":{}}}", loc.line, loc.col.0 + 1).unwrap();
+}
+
+/// Writes the location of `frame` like `push_location`, preceded by a `function` field if
+/// `with_function` is set.
+fn push_frame(out: &mut String, frame: &BacktraceFrame, with_function: bool) {
+    out.push('{');
+    if with_function {
+        out.push_str("\"function\":");
+        push_str(out, &frame.function);
+        out.push(',');
+    }
+    out.push_str("\"file\":");
+    push_str(out, &frame.file);
+    write!(out, ",\"line\":{},\"column\":{}}}", frame.line, frame.column).unwrap();
+}
+
+fn push_bytes(out: &mut String, bytes: &[u8]) {
+    out.push('[');
+    for (idx, byte) in bytes.iter().enumerate() {
+        if idx > 0 {
+            out.push(',');
+        }
+        write!(out, "{}", byte).unwrap();
+    }
+    out.push(']');
+}
+
+fn push_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
@@ -25,16 +25,23 @@ extern crate byteorder;
 extern crate regex;
 #[macro_use]
 extern crate lazy_static;
+extern crate sha1;
 
 mod cast;
 mod constraints;
+mod corpus;
+mod coverage;
 mod error;
 mod eval_context;
 mod executor;
 mod format_executor;
+mod json;
 mod place;
+mod regression;
 mod memory;
+mod merge;
 mod operator;
+mod search;
 mod step;
 mod terminator;
 mod traits;
@@ -44,6 +51,7 @@ mod driver;
 pub use error::{
     EvalError,
     EvalResult,
+    PanicLocation,
     StaticEvalError,
 };
 
@@ -54,9 +62,22 @@ pub use eval_context::{
     StackPopCleanup,
 };
 
+pub use coverage::{
+    CoverageReport,
+    FunctionCoverage,
+};
+
 pub use executor::{
+    BacktraceFrame,
     ExecutionComplete,
     ExecutionConfig,
+    ExplorationStatus,
+    Finding,
+    TestSummary,
+};
+
+pub use search::{
+    SearchStrategyKind,
 };
 
 pub use place::{
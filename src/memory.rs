@@ -24,10 +24,10 @@ impl fmt::Display for AllocId {
     }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub struct AbstractVariable(pub u32);
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum SByte {
     Concrete(u8),
     Abstract(AbstractVariable),
@@ -65,13 +65,13 @@ pub enum StaticKind {
     Immutable,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct MemoryPointer {
     pub alloc_id: AllocId,
     pub offset: PointerOffset,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum PointerOffset {
     /// Offset in bytes.
     Concrete(u64),
@@ -114,22 +114,22 @@ impl MemoryPointer {
         }
     }
 
-    pub fn wrapping_signed_offset<'tcx>(self, i: i64, layout: &TargetDataLayout) -> Self {
+    pub fn wrapping_signed_offset<'tcx>(self, i: i64, layout: &TargetDataLayout) -> EvalResult<'tcx, Self> {
         match self.offset {
             PointerOffset::Concrete(self_offset) => {
-                MemoryPointer::new(self.alloc_id, value::wrapping_signed_offset(self_offset, i, layout))
+                Ok(MemoryPointer::new(self.alloc_id, value::wrapping_signed_offset(self_offset, i, layout)))
             }
-            _ => unimplemented!(),
+            _ => Err(self.abstract_offset_error("offset arithmetic")),
         }
     }
 
-    pub fn overflowing_signed_offset<'tcx>(self, i: i128, layout: &TargetDataLayout) -> (Self, bool) {
+    pub fn overflowing_signed_offset<'tcx>(self, i: i128, layout: &TargetDataLayout) -> EvalResult<'tcx, (Self, bool)> {
         match self.offset {
             PointerOffset::Concrete(self_offset) => {
                 let (res, over) = value::overflowing_signed_offset(self_offset, i, layout);
-                (MemoryPointer::new(self.alloc_id, res), over)
+                Ok((MemoryPointer::new(self.alloc_id, res), over))
             }
-            _ => unimplemented!(),
+            _ => Err(self.abstract_offset_error("offset arithmetic")),
         }
     }
 
@@ -138,17 +138,17 @@ impl MemoryPointer {
             PointerOffset::Concrete(self_offset) => {
                 Ok(MemoryPointer::new(self.alloc_id, value::signed_offset(self_offset, i, layout)?))
             }
-            _ => unimplemented!(),
+            _ => Err(self.abstract_offset_error("offset arithmetic")),
         }
     }
 
-    pub fn overflowing_offset<'tcx>(self, i: u64, layout: &TargetDataLayout) -> (Self, bool) {
+    pub fn overflowing_offset<'tcx>(self, i: u64, layout: &TargetDataLayout) -> EvalResult<'tcx, (Self, bool)> {
         match self.offset {
             PointerOffset::Concrete(self_offset) => {
                 let (res, over) = value::overflowing_offset(self_offset, i, layout);
-                (MemoryPointer::new(self.alloc_id, res), over)
+                Ok((MemoryPointer::new(self.alloc_id, res), over))
             }
-            _ => unimplemented!(),
+            _ => Err(self.abstract_offset_error("offset arithmetic")),
         }
     }
 
@@ -157,10 +157,24 @@ impl MemoryPointer {
             PointerOffset::Concrete(offset) => {
                 Ok(MemoryPointer::new(self.alloc_id, value::offset(offset, i, layout)?))
             }
-            _ => unimplemented!(),
+            _ => Err(self.abstract_offset_error("offset arithmetic")),
         }
     }
 
+    pub fn concrete_offset<'tcx>(self, operation: &str) -> EvalResult<'tcx, u64> {
+        match self.offset {
+            PointerOffset::Concrete(offset) => Ok(offset),
+            _ => Err(self.abstract_offset_error(operation)),
+        }
+    }
+
+    /// The error for an operation that has no symbolic encoding yet and was given a pointer
+    /// whose offset depends on the input.
+    fn abstract_offset_error<'tcx>(self, operation: &str) -> EvalError<'tcx> {
+        EvalError::Unimplemented(
+            format!("{} on a pointer at an input-dependent offset into allocation {}", operation, self.alloc_id))
+    }
+
     pub fn to_value_with_vtable(self, vtable: MemoryPointer) -> Value {
         Value::ByValPair(PrimVal::Ptr(self), PrimVal::Ptr(vtable))
     }
@@ -308,8 +322,14 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
         } else {
             match tcx.alloc_map.lock().get(ptr.alloc_id) {
                 None => panic!("missing allocation {:?}", ptr.alloc_id),
-                Some(AllocType::Static(_)) => unimplemented!(),
-                Some(AllocType::Function(_)) => unimplemented!(),
+                Some(AllocType::Static(_)) => {
+                    return Err(EvalError::Unimplemented(
+                        "constants that point to statics".to_string()));
+                }
+                Some(AllocType::Function(_)) => {
+                    return Err(EvalError::Unimplemented(
+                        "constants that point to functions".to_string()));
+                }
                 Some(AllocType::Memory(alloc)) => {
                     let size = alloc.bytes.len() as u64;
                     let static_kind = match alloc.runtime_mutability {
@@ -331,9 +351,10 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
                     // XXX
                     new_alloc.undef_mask.set_range_inbounds(0, size, true);
 
-                    for _ in alloc.relocations.iter() {
+                    if !alloc.relocations.is_empty() {
                         // TODO
-                        unimplemented!()
+                        return Err(EvalError::Unimplemented(
+                            "constants that contain pointers".to_string()));
                     }
 
                     let id = self.next_id;
@@ -350,10 +371,7 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
     // when reallocating/deallocating any others.
     pub fn reallocate(&mut self, ptr: MemoryPointer, new_size: u64, align: u64) -> EvalResult<'tcx, MemoryPointer> {
         assert!(align.is_power_of_two());
-        let ptr_offset = match ptr.offset {
-            PointerOffset::Concrete(offset) => offset,
-            _ => unimplemented!(),
-        };
+        let ptr_offset = ptr.concrete_offset("reallocation")?;
 
         // TODO(solson): Report error about non-__rust_allocate'd pointer.
         if ptr_offset != 0 {
@@ -387,10 +405,7 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
 
     // TODO(solson): See comment on `reallocate`.
     pub fn deallocate(&mut self, ptr: MemoryPointer) -> EvalResult<'tcx> {
-        let ptr_offset = match ptr.offset {
-            PointerOffset::Concrete(offset) => offset,
-            _ => unimplemented!(),
-        };
+        let ptr_offset = ptr.concrete_offset("deallocation")?;
 
         if ptr_offset != 0 {
             // TODO(solson): Report error about non-__rust_allocate'd pointer.
@@ -422,10 +437,7 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
     }
 
     pub fn check_align(&self, ptr: MemoryPointer, align: u64, len: u64) -> EvalResult<'tcx> {
-        let ptr_offset = match ptr.offset {
-            PointerOffset::Concrete(offset) => offset,
-            _ => unimplemented!(),
-        };
+        let ptr_offset = ptr.concrete_offset("alignment check")?;
 
         let alloc = self.get(ptr.alloc_id)?;
         // check whether the memory was marked as packed
@@ -468,10 +480,7 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
         let alloc = self.get(ptr.alloc_id)?;
         let allocation_size = alloc.bytes.len() as u64;
 
-        let ptr_offset = match ptr.offset {
-            PointerOffset::Concrete(offset) => offset,
-            _ => unimplemented!(),
-        };
+        let ptr_offset = ptr.concrete_offset("bounds check")?;
 
         if ptr_offset > allocation_size {
             return Err(EvalError::PointerOutOfBounds { ptr, access, allocation_size });
@@ -479,17 +488,15 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
         Ok(())
     }
 
-    pub(crate) fn mark_packed(&mut self, ptr: MemoryPointer, len: u64) {
-        let ptr_offset = match ptr.offset {
-            PointerOffset::Concrete(offset) => offset,
-            _ => unimplemented!(),
-        };
+    pub(crate) fn mark_packed(&mut self, ptr: MemoryPointer, len: u64) -> EvalResult<'tcx> {
+        let ptr_offset = ptr.concrete_offset("marking memory as packed")?;
 
         self.packed.insert(Entry {
             alloc_id: ptr.alloc_id,
             packed_start: ptr_offset,
             packed_end: ptr_offset + len,
         });
+        Ok(())
     }
 
     pub(crate) fn clear_packed(&mut self) {
@@ -510,7 +517,7 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                     mir::BinOp::Add,
                     PrimVal::Abstract(sbytes),
                     PrimVal::Bytes(i as u128),
-                    PrimValKind::U64);
+                    PrimValKind::U64)?;
                 if let PrimVal::Abstract(osbytes) = new_offset {
                     Ok(MemoryPointer::new_abstract(ptr.alloc_id, osbytes))
                 } else {
@@ -679,10 +686,7 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
 
         let alloc = self.get(ptr.alloc_id)?;
 
-        let ptr_offset = match ptr.offset {
-            PointerOffset::Concrete(offset) => offset,
-            _ => unimplemented!(),
-        };
+        let ptr_offset = ptr.concrete_offset("read")?;
 
         assert_eq!(ptr_offset as usize as u64, ptr_offset);
         assert_eq!(size as usize as u64, size);
@@ -701,10 +705,7 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
         let alloc = self.get_mut(ptr.alloc_id)?;
 
         assert_eq!(size as usize as u64, size);
-        let ptr_offset = match ptr.offset {
-            PointerOffset::Concrete(offset) => offset,
-            _ => unimplemented!(),
-        };
+        let ptr_offset = ptr.concrete_offset("write")?;
 
         assert_eq!(ptr_offset as usize as u64, ptr_offset);
         let ptr_offset = ptr_offset as usize;
@@ -736,30 +737,17 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
         self.get_bytes_unchecked_mut(ptr, size, align)
     }
 
-    pub fn write_fresh_abstract_bytes(&mut self, ptr: MemoryPointer, size: u64)
-        -> EvalResult<'tcx>
-    {
-        let mut abytes = Vec::new();
-        for _ in 0..(size as usize){
-            abytes.push(self.constraints.fresh_stdin_byte());
-        }
-
-        let sbytes = self.get_bytes_mut(ptr, size, 1)?;
-        for idx in 0..(size as usize){
-            sbytes[idx] = abytes[idx];
-        }
-
-        Ok(())
-    }
-
     pub fn write_fresh_symbolic_var(&mut self, ptr: MemoryPointer, size: u64, label: String, ty: ty::Ty<'tcx>)
         -> EvalResult<'tcx>
     {
         let abytes = self.constraints.fresh_symbolic_var(label, size as u32, ty);
-        let sbytes = self.get_bytes_mut(ptr, size, 1)?;
-        for idx in 0..(size as usize) {
-            sbytes[idx] = abytes[idx];
-        }
+        self.write_sbytes(ptr, &abytes)
+    }
+
+    /// Writes bytes that may be abstract, unlike `write_bytes`.
+    pub fn write_sbytes(&mut self, ptr: MemoryPointer, src: &[SByte]) -> EvalResult<'tcx> {
+        let sbytes = self.get_bytes_mut(ptr, src.len() as u64, 1)?;
+        sbytes.copy_from_slice(src);
         Ok(())
     }
 }
@@ -818,14 +806,23 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
         let src = src.to_ptr()?;
         let dest = dest.to_ptr()?;
 
-        if let PointerOffset::Abstract(_) = dest.offset {
-            unimplemented!()
-        }
-
         if let PointerOffset::Abstract(_) = src.offset {
             return self.abstract_copy(src, dest, size, align);
         }
 
+        if let PointerOffset::Abstract(_) = dest.offset {
+            if src.alloc_id == dest.alloc_id ||
+                self.relocations(src, size)?.count() != 0 ||
+                !self.get(dest.alloc_id)?.relocations.is_empty()
+            {
+                let dest = self.resolve_abstract_offset(dest, size)?;
+                return self.copy(PrimVal::Ptr(src), PrimVal::Ptr(dest), size, align);
+            }
+            self.check_defined(src, size)?;
+            let sbytes = self.get_bytes_unchecked(src, size, align)?.to_vec();
+            return self.write_sbytes_to_abstract_ptr(dest, &sbytes);
+        }
+
         self.check_relocation_edges(src, size)?;
 
         // first copy the relocations to a temporary buffer, because
@@ -842,7 +839,7 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
                     })
                         .collect()
                 }
-                _ => unimplemented!(),
+                _ => bug!("copy between abstract offsets is handled above"),
             };
 
         let src_bytes = self.get_bytes_unchecked(src, size, align)?.as_ptr();
@@ -867,11 +864,12 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
         Ok(())
     }
 
-    fn abstract_copy(&mut self, src: MemoryPointer, dest: MemoryPointer, size: u64, _align: u64)
+    fn abstract_copy(&mut self, src: MemoryPointer, dest: MemoryPointer, size: u64, align: u64)
                      -> EvalResult<'tcx>
     {
-        if src.alloc_id == dest.alloc_id {
-            unimplemented!()
+        if src.alloc_id == dest.alloc_id || !self.get(src.alloc_id)?.relocations.is_empty() {
+            let src = self.resolve_abstract_offset(src, size)?;
+            return self.copy(PrimVal::Ptr(src), PrimVal::Ptr(dest), size, align);
         }
 
         let arr = self.symbolize_allocation(src.alloc_id)?;
@@ -883,14 +881,18 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
                         mir::BinOp::Add,
                         PrimVal::Bytes(idx as u128),
                         PrimVal::Abstract(src_offset),
-                        PrimValKind::U64);
+                        PrimValKind::U64)?;
 
                     let sbyte = self.constraints.add_array_element_constraint(arr, abs_idx);
                     self.get_bytes_mut(dest.offset(idx, self.layout)?, 1, 1)?[0] = sbyte;
                 }
 
             }
-            _ => unimplemented!(),
+            (PointerOffset::Abstract(_), PointerOffset::Abstract(_)) => {
+                let dest = self.resolve_abstract_offset(dest, size)?;
+                return self.abstract_copy(src, dest, size, align);
+            }
+            (PointerOffset::Concrete(_), _) => bug!("abstract_copy with concrete source offset"),
         }
 
         Ok(())
@@ -902,7 +904,8 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
         {
             let alloc = self.get(alloc_id)?;
             if !alloc.relocations.is_empty() {
-                unimplemented!()
+                return Err(EvalError::Unimplemented(
+                    "input-dependent access to an allocation that holds pointers".to_string()));
             }
 
             for idx in 0..alloc.bytes.len() {
@@ -916,22 +919,269 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
         Ok(arr)
     }
 
-    pub fn read_c_str(&self, _ptr: MemoryPointer) -> EvalResult<'tcx, &[u8]> {
-        unimplemented!()
-        /*
-        let alloc = self.get(ptr.alloc_id)?;
-        assert_eq!(ptr.offset as usize as u64, ptr.offset);
-        let offset = ptr.offset as usize;
-        match alloc.bytes[offset..].iter().position(|&c| c == SByte::Concrete(0)) {
-            Some(size) => {
-                if self.relocations(ptr, (size + 1) as u64)?.count() != 0 {
-                    return Err(EvalError::ReadPointerAsBytes);
+    /// Turns the abstract offset of `ptr` into a concrete one, for accesses of `size` bytes
+    /// that the array theory cannot model, e.g. because the allocation holds pointers. The
+    /// solver only reasons about the offset; the pointers themselves stay concrete.
+    ///
+    /// If the path constraints leave only one in-bounds offset, returns `ptr` with that
+    /// offset. Otherwise asks `step` to fork over the in-bounds offsets `ptr` can have, unless
+    /// there are more than `MAX_ABSTRACT_FORKS`. Reads should prefer `read_table_entry`, which
+    /// forks far less.
+    pub(crate) fn resolve_abstract_offset(&self, ptr: MemoryPointer, size: u64)
+                                          -> EvalResult<'tcx, MemoryPointer>
+    {
+        let offset = match ptr.offset {
+            PointerOffset::Concrete(_) => return Ok(ptr),
+            PointerOffset::Abstract(sbytes) => PrimVal::Abstract(sbytes),
+        };
+
+        let allocation_size = self.get(ptr.alloc_id)?.bytes.len() as u64;
+        let out_of_bounds = EvalError::PointerOutOfBounds { ptr, access: true, allocation_size };
+        if size > allocation_size {
+            return Err(out_of_bounds);
+        }
+
+        let kind = PrimValKind::U64;
+        let max = (allocation_size - size) as u128;
+        match self.constraints.unique_value_up_to(offset, kind, max, MAX_ABSTRACT_FORKS + 1) {
+            Ok(offset) => Ok(MemoryPointer::new(ptr.alloc_id, offset as u64)),
+            Err(ref candidates) if candidates.len() > MAX_ABSTRACT_FORKS => {
+                Err(EvalError::Unimplemented(format!(
+                    "access at an input-dependent offset with more than {} possible values",
+                    MAX_ABSTRACT_FORKS)))
+            }
+            Err(candidates) => Err(EvalError::NeedsConcreteValue {
+                value: offset,
+                kind,
+                candidates,
+                otherwise: Box::new(out_of_bounds),
+            }),
+        }
+    }
+
+    /// Reads the integer or thin pointer of `size` bytes at the abstract offset of `ptr` from
+    /// an allocation that may hold pointers, like a table of references.
+    ///
+    /// The solver only sees pointers as offsets into a concrete allocation. Every aligned
+    /// entry the read could hit is grouped by the allocation it points into, or by none for
+    /// integers. Paths fork over the groups the offset can select, at most
+    /// `MAX_ABSTRACT_FORKS` of them, and the value read is an if-then-else over the entries
+    /// of the selected group. A table whose entries all point into the same allocation, or
+    /// hold no pointers at all, is read without forking.
+    pub(crate) fn read_table_entry(&mut self, ptr: MemoryPointer, size: u64)
+                                   -> EvalResult<'tcx, PrimVal>
+    {
+        let offset = match ptr.offset {
+            PointerOffset::Abstract(sbytes) => PrimVal::Abstract(sbytes),
+            PointerOffset::Concrete(_) => bug!("read_table_entry with concrete offset"),
+        };
+        let kind = match size {
+            1 => PrimValKind::U8,
+            2 => PrimValKind::U16,
+            4 => PrimValKind::U32,
+            8 => PrimValKind::U64,
+            _ => bug!("table entries are at most 8 bytes wide"),
+        };
+
+        let allocation_size = self.get(ptr.alloc_id)?.bytes.len() as u64;
+        let out_of_bounds = EvalError::PointerOutOfBounds { ptr, access: true, allocation_size };
+        let entries = self.table_entries(ptr.alloc_id, size)?;
+        let mut targets: Vec<Option<AllocId>> = Vec::new();
+        for &(_, target, _) in &entries {
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+
+        // The index into `targets` of the entry at `offset`, or `targets.len()` if there is
+        // no entry there.
+        let mut group = PrimVal::Bytes(targets.len() as u128);
+        for &(entry_offset, target, _) in entries.iter().rev() {
+            let idx = targets.iter().position(|&t| t == target).expect("collected above");
+            let hit = self.constraints.add_binop_constraint(
+                mir::BinOp::Eq, offset, PrimVal::Bytes(entry_offset as u128), PrimValKind::U64)?;
+            group = self.constraints.add_if_then_else(
+                hit, PrimValKind::U64, PrimVal::Bytes(idx as u128), group)?;
+        }
+
+        let max = targets.len() as u128;
+        let target = match self.constraints.unique_value_up_to(
+            group, PrimValKind::U64, max, MAX_ABSTRACT_FORKS + 1)
+        {
+            Ok(idx) if idx == max => return Err(out_of_bounds),
+            Ok(idx) => targets[idx as usize],
+            Err(ref candidates) if candidates.len() > MAX_ABSTRACT_FORKS => {
+                return Err(EvalError::Unimplemented(format!(
+                    "read at an input-dependent offset from a table pointing into more than \
+                     {} allocations", MAX_ABSTRACT_FORKS)));
+            }
+            Err(candidates) => return Err(EvalError::NeedsConcreteValue {
+                value: group,
+                kind: PrimValKind::U64,
+                candidates,
+                otherwise: Box::new(out_of_bounds),
+            }),
+        };
+
+        // The path constraints put `offset` at one of these entries, so the last one needs no
+        // condition.
+        let mut selected = entries.into_iter().filter(|&(_, t, _)| t == target).rev();
+        let (_, _, last) = selected.next().expect("the group has an entry");
+        let mut value = last;
+        for (entry_offset, _, entry_value) in selected {
+            if entry_value == value {
+                continue;
+            }
+            let hit = self.constraints.add_binop_constraint(
+                mir::BinOp::Eq, offset, PrimVal::Bytes(entry_offset as u128), PrimValKind::U64)?;
+            value = self.constraints.add_if_then_else(hit, kind, entry_value, value)?;
+        }
+
+        Ok(match (target, value) {
+            (None, value) => value,
+            (Some(alloc_id), PrimVal::Bytes(n)) => {
+                PrimVal::Ptr(MemoryPointer::new(alloc_id, n as u64))
+            }
+            (Some(alloc_id), PrimVal::Abstract(sbytes)) => {
+                PrimVal::Ptr(MemoryPointer::new_abstract(alloc_id, sbytes))
+            }
+            (Some(_), _) => bug!("table entries are bytes"),
+        })
+    }
+
+    /// Every aligned, defined value of `size` bytes in the allocation that does not overlap
+    /// part of a pointer: its offset, the allocation it points into if it is a pointer, and
+    /// its bytes as an integer.
+    fn table_entries(&self, alloc_id: AllocId, size: u64)
+                     -> EvalResult<'tcx, Vec<(u64, Option<AllocId>, PrimVal)>>
+    {
+        let align = self.int_align(size)?;
+        let pointer_size = self.pointer_size();
+        let endianness = self.endianness();
+        let alloc = self.get(alloc_id)?;
+        let allocation_size = alloc.bytes.len() as u64;
+
+        let mut entries = Vec::new();
+        let mut entry_offset = 0;
+        while entry_offset + size <= allocation_size {
+            let ptr = MemoryPointer::new(alloc_id, entry_offset);
+            let start = entry_offset.saturating_sub(pointer_size - 1);
+            let mut overlapping = alloc.relocations.range(start..entry_offset + size);
+            let target = match (overlapping.next(), overlapping.next()) {
+                (None, _) => Some(None),
+                (Some((&offset, &target)), None)
+                    if offset == entry_offset && size == pointer_size => Some(Some(target)),
+                _ => None,
+            };
+            if let Some(target) = target {
+                if self.check_defined(ptr, size).is_ok() {
+                    let sbytes = self.get_bytes_unchecked(ptr, size, 1)?;
+                    let value = if let Ok(bytes) = concrete_bytes(sbytes) {
+                        PrimVal::Bytes(read_target_uint(endianness, &bytes).unwrap())
+                    } else {
+                        let mut buffer = [SByte::Concrete(0); 8];
+                        for (idx, &sbyte) in sbytes.iter().enumerate() {
+                            let dest_idx = if let layout::Endian::Big = endianness {
+                                size as usize - 1 - idx
+                            } else {
+                                idx
+                            };
+                            buffer[dest_idx] = sbyte;
+                        }
+                        PrimVal::Abstract(buffer)
+                    };
+                    entries.push((entry_offset, target, value));
                 }
-                self.check_defined(ptr, (size + 1) as u64)?;
-                Ok(&alloc.bytes[offset..offset + size])
-            },
-            None => Err(EvalError::UnterminatedCString(ptr)),
-        } */
+            }
+            entry_offset += align;
+        }
+        Ok(entries)
+    }
+
+    /// Reads `size` bytes at the abstract offset of `ptr` through the array theory. The
+    /// allocation must not hold any pointers.
+    pub(crate) fn read_at_abstract_offset(&mut self, ptr: MemoryPointer, size: u64)
+                                          -> EvalResult<'tcx, PrimVal>
+    {
+        let offset = match ptr.offset {
+            PointerOffset::Abstract(sbytes) => PrimVal::Abstract(sbytes),
+            PointerOffset::Concrete(_) => bug!("read_at_abstract_offset with concrete offset"),
+        };
+        assert!(size <= 8, "abstract reads are at most 8 bytes wide");
+
+        let arr = self.symbolize_allocation(ptr.alloc_id)?;
+        let mut result_sbytes = [SByte::Concrete(0); 8];
+        for idx in 0..size {
+            let abs_idx = self.constraints.add_binop_constraint(
+                mir::BinOp::Add,
+                PrimVal::Bytes(idx as u128),
+                offset,
+                PrimValKind::U64)?;
+            let sbyte = self.constraints.add_array_element_constraint(arr, abs_idx);
+            let dest_idx = if let layout::Endian::Big = self.endianness() {
+                size - 1 - idx
+            } else {
+                idx
+            };
+            result_sbytes[dest_idx as usize] = sbyte;
+        }
+
+        Ok(PrimVal::Abstract(result_sbytes))
+    }
+
+    /// Stores `sbytes`, in memory order, at the abstract offset of `dest` and writes the
+    /// resulting array back to the allocation.
+    fn write_sbytes_to_abstract_ptr(&mut self, dest: MemoryPointer, sbytes: &[SByte])
+                                    -> EvalResult<'tcx>
+    {
+        let offset = match dest.offset {
+            PointerOffset::Abstract(offset) => PrimVal::Abstract(offset),
+            PointerOffset::Concrete(_) => bug!("write_sbytes_to_abstract_ptr with concrete offset"),
+        };
+
+        let mut arr = self.symbolize_allocation(dest.alloc_id)?;
+        for (idx, &sbyte) in sbytes.iter().enumerate() {
+            let abs_idx = self.constraints.add_binop_constraint(
+                mir::BinOp::Add,
+                PrimVal::Bytes(idx as u128),
+                offset,
+                PrimValKind::U64)?;
+
+            arr = self.constraints.store_array_element(arr, abs_idx, sbyte);
+        }
+
+        // now write the values of arr back to the dest allocation
+
+        let dest_alloc_len = self.get(dest.alloc_id)?.bytes.len();
+        for idx in 0..dest_alloc_len {
+            let sbyte = self.constraints.add_array_element_constraint(
+                arr, PrimVal::Bytes(idx as u128));
+
+            let ptr = MemoryPointer::new(dest.alloc_id, idx as u64);
+            self.get_bytes_mut(ptr, 1, 1)?[0] = sbyte;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the NUL-terminated string at `ptr`, without the NUL. Every byte before the NUL
+    /// has to be concrete.
+    pub fn read_c_str(&self, ptr: MemoryPointer) -> EvalResult<'tcx, Vec<u8>> {
+        self.check_bounds(ptr, false)?;
+        let offset = ptr.concrete_offset("C string read")? as usize;
+        let alloc = self.get(ptr.alloc_id)?;
+        let size = match alloc.bytes[offset..].iter().position(|&c| c == SByte::Concrete(0)) {
+            Some(size) => size as u64,
+            None => return Err(EvalError::UnterminatedCString(ptr)),
+        };
+        let bytes = self.get_bytes(ptr, size + 1, 1)?;
+        bytes[..size as usize].iter()
+            .map(|b| match *b {
+                SByte::Concrete(b) => Ok(b),
+                SByte::Abstract(_) => Err(EvalError::Unimplemented(
+                    "reading a C string with symbolic bytes".to_string())),
+            })
+            .collect()
     }
 
     pub fn read_bytes(&self, ptr: PrimVal, size: u64)
@@ -983,9 +1233,9 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
         }
         // Now we do the actual reading
         let bytes = if signed {
-            read_target_int(endianess, bytes).unwrap() as u128
+            read_target_int(endianess, &concrete_bytes(bytes)?).unwrap() as u128
         } else {
-            read_target_uint(endianess, bytes).unwrap()
+            read_target_uint(endianess, &concrete_bytes(bytes)?).unwrap()
         };
         // See if we got a pointer
         if size != self.pointer_size() {
@@ -1001,7 +1251,7 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
                         None => {},
                     }
                 }
-                PointerOffset::Abstract(_) => unimplemented!(),
+                PointerOffset::Abstract(_) => return Err(ptr.abstract_offset_error("pointer read")),
             }
         }
         // We don't. Just return the bytes.
@@ -1010,20 +1260,18 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
 
     pub fn read_ptr(&self, ptr: MemoryPointer) -> EvalResult<'tcx, PrimVal> {
         let size = self.pointer_size();
+        let ptr = self.resolve_abstract_offset(ptr, size)?;
         if self.check_defined(ptr, size).is_err() {
             return Ok(PrimVal::Undef);
         }
 
-        let ptr_offset = match ptr.offset {
-            PointerOffset::Concrete(offset) => offset,
-            _ => unimplemented!(),
-        };
+        let ptr_offset = ptr.concrete_offset("pointer read")?;
         let alloc = self.get(ptr.alloc_id)?;
 
         let endianness = self.endianness();
         if self.points_to_concrete(ptr, size)? {
             let bytes = self.get_bytes_unchecked(ptr, size, size)?;
-            let offset = read_target_uint(endianness, bytes).unwrap();
+            let offset = read_target_uint(endianness, &concrete_bytes(bytes)?).unwrap();
             assert_eq!(offset as u64 as u128, offset);
             let offset = offset as u64;
 
@@ -1037,7 +1285,7 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
 
             match alloc.relocations.get(&ptr_offset) {
                 Some(&alloc_id) => Ok(PrimVal::Ptr(MemoryPointer::new_abstract(alloc_id, sbytes))),
-                None => unimplemented!(),
+                None => Ok(PrimVal::Abstract(sbytes)),
             }
         }
     }
@@ -1056,7 +1304,7 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
                 self.get_mut(dest.alloc_id)?.relocations.insert(dest_offset, ptr.alloc_id);
                 Ok(())
             }
-            _ => unimplemented!(),
+            _ => Err(dest.abstract_offset_error("pointer write")),
         }
     }
 
@@ -1099,7 +1347,11 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
                         Ok(())
                     }
                     layout::Endian::Big => {
-                        unimplemented!()
+                        let dest_slice = self.get_bytes_mut(dest.to_ptr()?, size, align)?;
+                        for (dest_byte, &sbyte) in dest_slice.iter_mut().rev().zip(sbytes.iter()) {
+                            *dest_byte = sbyte;
+                        }
+                        Ok(())
                     }
                 }
             }
@@ -1114,61 +1366,46 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
         val: PrimVal,
         size: u64,
     ) -> EvalResult<'tcx> {
-        if let PointerOffset::Abstract(sbytes) = dest.offset {
-            let mut arr = self.symbolize_allocation(dest.alloc_id)?;
-            match val {
-                PrimVal::Ptr(_ptr) => unimplemented!(),
-
-                PrimVal::Bytes(n) => {
-                    // We need to mask here, or the byteorder crate can die when given a u64 larger
-                    // than fits in an integer of the requested size.
-                    let mask = match size {
-                        1 => !0u8 as u128,
-                        2 => !0u16 as u128,
-                        4 => !0u32 as u128,
-                        8 => !0u64 as u128,
-                        16 => !0,
-                        _ => bug!("unexpected PrimVal::Bytes size"),
-                    };
-
-                    let mut bytes = vec![0u8; size as usize];
-                    let endianness = self.endianness();
-                    Self::write_target_uint(endianness, &mut bytes[..], n & mask).unwrap();
+        let must_resolve = match val {
+            PrimVal::Ptr(_) | PrimVal::Undef => true,
+            _ => !self.get(dest.alloc_id)?.relocations.is_empty(),
+        };
+        if must_resolve {
+            let dest = self.resolve_abstract_offset(dest, size)?;
+            return self.write_primval(PrimVal::Ptr(dest), val, size);
+        }
 
-                    for idx in 0..size {
-                        let abs_idx = self.constraints.add_binop_constraint(
-                            mir::BinOp::Add,
-                            PrimVal::Bytes(idx as u128),
-                            PrimVal::Abstract(sbytes),
-                            PrimValKind::U64);
+        let sbytes: Vec<SByte> = match val {
+            PrimVal::Bytes(n) => {
+                // We need to mask here, or the byteorder crate can die when given a u64 larger
+                // than fits in an integer of the requested size.
+                let mask = match size {
+                    1 => !0u8 as u128,
+                    2 => !0u16 as u128,
+                    4 => !0u32 as u128,
+                    8 => !0u64 as u128,
+                    16 => !0,
+                    _ => bug!("unexpected PrimVal::Bytes size"),
+                };
 
-                        arr = self.constraints.store_array_element(
-                            arr, abs_idx, SByte::Concrete(bytes[idx as usize]));
-                    }
-                }
+                let mut bytes = vec![0u8; size as usize];
+                let endianness = self.endianness();
+                Self::write_target_uint(endianness, &mut bytes[..], n & mask).unwrap();
+                bytes.into_iter().map(SByte::Concrete).collect()
+            }
 
-                PrimVal::Abstract(_sbytes) => {
-                    unimplemented!()
+            PrimVal::Abstract(sbytes) => {
+                let mut sbytes = sbytes[.. size as usize].to_vec();
+                if let layout::Endian::Big = self.endianness() {
+                    sbytes.reverse();
                 }
-
-                PrimVal::Undef => unimplemented!(),
+                sbytes
             }
 
-            // now write the values of arr back to the dest allocation
-
-            let dest_alloc_len = self.get(dest.alloc_id)?.bytes.len();
-            for idx in 0..dest_alloc_len {
-                let sbyte = self.constraints.add_array_element_constraint(
-                    arr, PrimVal::Bytes(idx as u128));
-
-                let ptr = MemoryPointer::new(dest.alloc_id, idx as u64);
-                self.get_bytes_mut(ptr, 1, 1)?[0] = sbyte;
-            }
+            PrimVal::Ptr(_) | PrimVal::Undef => unreachable!(),
+        };
 
-            Ok(())
-        } else {
-            unreachable!()
-        }
+        self.write_sbytes_to_abstract_ptr(dest, &sbytes)
     }
 
     pub fn read_bool(&self, ptr: MemoryPointer) -> EvalResult<'tcx, PrimVal> {
@@ -1205,7 +1442,8 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
 
     pub fn read_int(&self, ptr: MemoryPointer, size: u64) -> EvalResult<'tcx, i128> {
         let align = self.int_align(size)?;
-        self.get_bytes(ptr, size, align).map(|b| read_target_int(self.endianness(), b).unwrap())
+        let bytes = concrete_bytes(self.get_bytes(ptr, size, align)?)?;
+        Ok(read_target_int(self.endianness(), &bytes).unwrap())
     }
 
     pub fn write_int(&mut self, ptr: MemoryPointer, n: i128, size: u64) -> EvalResult<'tcx> {
@@ -1234,7 +1472,8 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
 
     pub fn read_uint(&self, ptr: MemoryPointer, size: u64) -> EvalResult<'tcx, u128> {
         let align = self.int_align(size)?;
-        self.get_bytes(ptr, size, align).map(|b| read_target_uint(self.endianness(), b).unwrap())
+        let bytes = concrete_bytes(self.get_bytes(ptr, size, align)?)?;
+        Ok(read_target_uint(self.endianness(), &bytes).unwrap())
     }
 
     pub fn write_uint(&mut self, ptr: MemoryPointer, n: u128, size: u64) -> EvalResult<'tcx> {
@@ -1285,13 +1524,13 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
     }*/
 
     pub fn read_f32(&self, ptr: MemoryPointer) -> EvalResult<'tcx, f32> {
-        self.get_bytes(ptr, 4, self.layout.f32_align.abi())
-            .map(|b| read_target_f32(self.endianness(), b).unwrap())
+        let bytes = concrete_bytes(self.get_bytes(ptr, 4, self.layout.f32_align.abi())?)?;
+        Ok(read_target_f32(self.endianness(), &bytes).unwrap())
     }
 
     pub fn read_f64(&self, ptr: MemoryPointer) -> EvalResult<'tcx, f64> {
-        self.get_bytes(ptr, 8, self.layout.f64_align.abi())
-            .map(|b| read_target_f64(self.endianness(), b).unwrap())
+        let bytes = concrete_bytes(self.get_bytes(ptr, 8, self.layout.f64_align.abi())?)?;
+        Ok(read_target_f64(self.endianness(), &bytes).unwrap())
     }
 
     ////////////////////////////////////////////////////////////////////////////////
@@ -1317,83 +1556,137 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
     }
 }
 
-fn read_target_uint(endianness: layout::Endian, sbytes: &[SByte])
-                    -> Result<u128, io::Error>
-{
-    let mut source = Vec::with_capacity(sbytes.len());
+fn concrete_bytes<'tcx>(sbytes: &[SByte]) -> EvalResult<'tcx, Vec<u8>> {
+    let mut bytes = Vec::with_capacity(sbytes.len());
     for sb in sbytes {
         match *sb {
-            SByte::Concrete(b) => source.push(b),
-            SByte::Abstract(_v) => unimplemented!(),
+            SByte::Concrete(b) => bytes.push(b),
+            SByte::Abstract(_) => return Err(EvalError::Unimplemented(
+                "reading input-dependent bytes as a concrete value".to_string())),
         }
     }
-    let mut source = &source[..];
+    Ok(bytes)
+}
+
+fn read_target_uint(endianness: layout::Endian, mut source: &[u8])
+                    -> Result<u128, io::Error>
+{
     match endianness {
         layout::Endian::Little => source.read_uint128::<LittleEndian>(source.len()),
         layout::Endian::Big => source.read_uint128::<BigEndian>(source.len()),
     }
 }
 
-fn read_target_int(endianness: layout::Endian, sbytes: &[SByte])
+fn read_target_int(endianness: layout::Endian, mut source: &[u8])
                     -> Result<i128, io::Error>
 {
-    let mut source = Vec::with_capacity(sbytes.len());
-    for sb in sbytes {
-        match *sb {
-            SByte::Concrete(b) => source.push(b),
-            SByte::Abstract(_v) => unimplemented!(),
-        }
-    }
-    let mut source = &source[..];
     match endianness {
         layout::Endian::Little => source.read_int128::<LittleEndian>(source.len()),
         layout::Endian::Big => source.read_int128::<BigEndian>(source.len()),
     }
 }
 
-fn read_target_f32(endianness: layout::Endian, sbytes: &[SByte])
+fn read_target_f32(endianness: layout::Endian, mut source: &[u8])
                     -> Result<f32, io::Error>
 {
-    let mut source = Vec::with_capacity(sbytes.len());
-    for sb in sbytes {
-        match *sb {
-            SByte::Concrete(b) => source.push(b),
-            SByte::Abstract(_v) => unimplemented!(),
-        }
-    }
-    let mut source = &source[..];
     match endianness {
         layout::Endian::Little => source.read_f32::<LittleEndian>(),
         layout::Endian::Big => source.read_f32::<BigEndian>(),
     }
 }
 
-fn read_target_f64(endianness: layout::Endian, sbytes: &[SByte])
+fn read_target_f64(endianness: layout::Endian, mut source: &[u8])
                     -> Result<f64, io::Error>
 {
-    let mut source = Vec::with_capacity(sbytes.len());
-    for sb in sbytes {
-        match *sb {
-            SByte::Concrete(b) => source.push(b),
-            SByte::Abstract(_v) => unimplemented!(),
-        }
-    }
-    let mut source = &source[..];
     match endianness {
         layout::Endian::Little => source.read_f64::<LittleEndian>(),
         layout::Endian::Big => source.read_f64::<BigEndian>(),
     }
 }
 
+/// Merging
+impl<'a, 'tcx> Memory<'a, 'tcx> {
+    /// Whether `other` has the same allocations as `self`, differing at most in the values of
+    /// plain data bytes. Bytes that belong to pointers or hold boolean variables have to agree,
+    /// because merged bytes become 8-bit if-then-else values.
+    pub(crate) fn can_merge_with(&self, other: &Memory<'a, 'tcx>, bool_vars: &HashSet<u32>) -> bool {
+        if self.next_id != other.next_id ||
+            self.alloc_map.len() != other.alloc_map.len() ||
+            self.static_alloc != other.static_alloc ||
+            self.functions != other.functions ||
+            self.rustc_allocations != other.rustc_allocations ||
+            self.literal_alloc_cache != other.literal_alloc_cache
+        {
+            return false;
+        }
+
+        let is_bool = |b: &SByte| match *b {
+            SByte::Abstract(v) => bool_vars.contains(&v.0),
+            SByte::Concrete(_) => false,
+        };
+
+        for (id, a) in &self.alloc_map {
+            let b = match other.alloc_map.get(id) {
+                Some(b) => b,
+                None => return false,
+            };
+            if a.bytes.len() != b.bytes.len() ||
+                a.relocations != b.relocations ||
+                a.undef_mask != b.undef_mask ||
+                a.align != b.align ||
+                a.static_kind != b.static_kind
+            {
+                return false;
+            }
+            for (offset, (x, y)) in a.bytes.iter().zip(b.bytes.iter()).enumerate() {
+                if x == y {
+                    continue;
+                }
+                let offset = offset as u64;
+                let start = offset.saturating_sub(self.pointer_size() - 1);
+                if a.relocations.range(start..offset + 1).next().is_some() ||
+                    is_bool(x) || is_bool(y)
+                {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Makes every byte that differs between `self` and `other` an abstract value that is
+    /// `guard ? self : other`. Requires `can_merge_with` to hold.
+    pub(crate) fn merge_from(&mut self, other: &Memory<'a, 'tcx>, guard: PrimVal) {
+        let Memory { ref mut alloc_map, ref mut constraints, .. } = *self;
+        for (id, a) in alloc_map.iter_mut() {
+            let b = &other.alloc_map[id];
+            for (x, y) in a.bytes.iter_mut().zip(b.bytes.iter()) {
+                if *x == *y {
+                    continue;
+                }
+                let merged = constraints.add_if_then_else(
+                    guard, PrimValKind::U8, sbyte_to_primval(*x), sbyte_to_primval(*y))
+                    .expect("byte-wide values can always be merged");
+                if let PrimVal::Abstract(sbytes) = merged {
+                    *x = sbytes[0];
+                }
+            }
+        }
+    }
+}
+
+fn sbyte_to_primval(sbyte: SByte) -> PrimVal {
+    let mut sbytes = [SByte::Concrete(0); 8];
+    sbytes[0] = sbyte;
+    PrimVal::Abstract(sbytes)
+}
+
 /// Relocations
 impl<'a, 'tcx> Memory<'a, 'tcx> {
     fn relocations(&self, ptr: MemoryPointer, size: u64)
         -> EvalResult<'tcx, btree_map::Range<u64, AllocId>>
     {
-        let ptr_offset = match ptr.offset {
-            PointerOffset::Concrete(offset) => offset,
-            _ => unimplemented!(),
-        };
+        let ptr_offset = ptr.concrete_offset("relocation lookup")?;
 
         let start = ptr_offset.saturating_sub(self.pointer_size() - 1);
         let end = ptr_offset + size;
@@ -1412,10 +1705,7 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
         if keys.is_empty() { return Ok(()); }
 
         // Find the start and end of the given range and its outermost relocations.
-        let start = match ptr.offset {
-            PointerOffset::Concrete(offset) => offset,
-            _ => unimplemented!(),
-        };
+        let start = ptr.concrete_offset("relocation clearing")?;
         let end = start + size;
         let first = *keys.first().unwrap();
         let last = *keys.last().unwrap() + self.pointer_size();
@@ -1463,7 +1753,7 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
                 }
                 Ok(())
             }
-            _ => unimplemented!(),
+            _ => Err(dest.abstract_offset_error("copy of definedness")),
         }
     }
 
@@ -1476,7 +1766,7 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
                 }
                 Ok(())
             }
-            _ => unimplemented!(),
+            _ => Err(ptr.abstract_offset_error("definedness check")),
         }
     }
 
@@ -1501,7 +1791,7 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
                     // nothing to do
                     Ok(())
                 } else {
-                    unimplemented!()
+                    Err(ptr.abstract_offset_error("definedness update"))
                 }
             }
         }
@@ -1515,7 +1805,11 @@ impl<'a, 'tcx> Memory<'a, 'tcx> {
 type Block = u64;
 const BLOCK_SIZE: u64 = 64;
 
-#[derive(Clone, Debug)]
+/// The most paths an access at an input-dependent offset forks into. Beyond that, the access
+/// is reported as unimplemented.
+const MAX_ABSTRACT_FORKS: usize = 64;
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct UndefMask {
     blocks: Vec<Block>,
     len: u64,
@@ -0,0 +1,118 @@
+//! This module contains the `EvalContext` methods for merging two execution states that have
+//! reached the same program point.
+
+use rustc::mir;
+use rustc_data_structures::indexed_vec::Idx;
+
+use eval_context::EvalContext;
+use memory::SByte;
+use value::{PrimVal, PrimValKind, Value};
+
+impl<'a, 'tcx> EvalContext<'a, 'tcx> {
+    /// Whether the current frame is about to enter a basic block with several predecessors.
+    pub(crate) fn at_join_point(&self) -> bool {
+        match self.stack.last() {
+            Some(frame) => {
+                frame.stmt == 0 && frame.mir.predecessors_for(frame.block).len() > 1
+            }
+            None => false,
+        }
+    }
+
+    /// Tries to merge `other` into `self`, so that a single state covers both paths.
+    ///
+    /// Both states must have identical stacks and allocations. Locals and memory bytes that
+    /// differ become if-then-else values over a fresh guard, and the path condition becomes
+    /// the disjunction of the two path conditions. Returns `false`, leaving `self` untouched,
+    /// if the states are not compatible.
+    pub(crate) fn try_merge(&mut self, other: &EvalContext<'a, 'tcx>) -> bool {
+        if self.stack.len() != other.stack.len() ||
+            self.globals != other.globals ||
+            self.env_vars != other.env_vars ||
+            self.program_args != other.program_args ||
+            self.symbolic_env_vars != other.symbolic_env_vars ||
+            self.files != other.files ||
+            self.open_files != other.open_files ||
+            self.errno != other.errno ||
+            self.stdin != other.stdin
+        {
+            return false;
+        }
+
+        let mut merged_locals = Vec::new();
+        for (frame_idx, (a, b)) in self.stack.iter().zip(other.stack.iter()).enumerate() {
+            if a.instance != b.instance ||
+                a.block != b.block ||
+                a.stmt != b.stmt ||
+                a.span != b.span ||
+                a.return_to_block != b.return_to_block ||
+                a.return_place != b.return_place
+            {
+                return false;
+            }
+
+            for (local_idx, (x, y)) in a.locals.iter().zip(b.locals.iter()).enumerate() {
+                if x == y {
+                    continue;
+                }
+                let (x, y) = match (*x, *y) {
+                    (Value::ByVal(x), Value::ByVal(y)) => (x, y),
+                    _ => return false,
+                };
+                let ty = a.mir.local_decls[mir::Local::new(local_idx)].ty;
+                let ty = self.monomorphize(ty, a.instance.substs);
+                let kind = match self.ty_to_primval_kind(ty) {
+                    Ok(kind) => kind,
+                    Err(_) => return false,
+                };
+                if !is_mergeable(x, kind) || !is_mergeable(y, kind) {
+                    return false;
+                }
+                merged_locals.push((frame_idx, local_idx, kind));
+            }
+        }
+
+        let bool_vars = self.memory.constraints.bool_variables();
+        if !self.memory.constraints.can_merge_with(&other.memory.constraints) ||
+            !self.memory.can_merge_with(&other.memory, &bool_vars)
+        {
+            return false;
+        }
+
+        let guard = self.memory.constraints.merge_path_conditions(&other.memory.constraints);
+        self.memory.merge_from(&other.memory, guard);
+
+        for (frame_idx, local_idx, kind) in merged_locals {
+            let then_branch = self.stack[frame_idx].locals[local_idx];
+            let else_branch = other.stack[frame_idx].locals[local_idx];
+            if let (Value::ByVal(x), Value::ByVal(y)) = (then_branch, else_branch) {
+                let merged = self.memory.constraints.add_if_then_else(guard, kind, x, y)
+                    .expect("is_mergeable checked the kind and both branches");
+                self.stack[frame_idx].locals[local_idx] = Value::ByVal(merged);
+            }
+        }
+
+        self.steps_remaining = ::std::cmp::min(self.steps_remaining, other.steps_remaining);
+        true
+    }
+}
+
+/// Whether `val` can be a branch of an if-then-else constraint of the given kind.
+fn is_mergeable(val: PrimVal, kind: PrimValKind) -> bool {
+    use value::PrimValKind::*;
+    match kind {
+        Bool => match val {
+            PrimVal::Bytes(_) => true,
+            PrimVal::Abstract(sbytes) => match sbytes[0] {
+                SByte::Abstract(_) => true,
+                SByte::Concrete(_) => false,
+            },
+            _ => false,
+        },
+        U8 | I8 | U16 | I16 | U32 | I32 | U64 | I64 => match val {
+            PrimVal::Bytes(_) | PrimVal::Abstract(_) => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
@@ -192,7 +192,10 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                     let right = right.to_ptr()?;
                     let (left_offset, right_offset) = match (left.offset, right.offset) {
                         (PointerOffset::Concrete(l), PointerOffset::Concrete(r)) => (l, r),
-                        _ => unimplemented!(),
+                        _ => {
+                            let msg = format!("unimplemented binary op on pointers at input-dependent offsets {:?}: {:?}, {:?}", bin_op, left, right);
+                            return Err(EvalError::Unimplemented(msg));
+                        }
                     };
 
 
@@ -323,17 +326,20 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
 
         let left_offset = match left.offset {
             PointerOffset::Concrete(n) => n,
-            _ => unimplemented!(),
+            _ => {
+                let msg = format!("unimplemented binary op on pointer at an input-dependent offset {:?}: {:?}, {:?}", bin_op, left, right);
+                return Err(EvalError::Unimplemented(msg));
+            }
         };
 
         Ok(match bin_op {
             Sub =>
                 // The only way this can overflow is by underflowing, so signedness of the right operands does not matter
-                map_to_primval(left.overflowing_signed_offset(-right, self.memory.layout)),
+                map_to_primval(left.overflowing_signed_offset(-right, self.memory.layout)?),
             Add if signed =>
-                map_to_primval(left.overflowing_signed_offset(right, self.memory.layout)),
+                map_to_primval(left.overflowing_signed_offset(right, self.memory.layout)?),
             Add if !signed =>
-                map_to_primval(left.overflowing_offset(right as u64, self.memory.layout)),
+                map_to_primval(left.overflowing_offset(right as u64, self.memory.layout)?),
 
             BitAnd if !signed => {
                 let base_mask : u64 = !(self.memory.get(left.alloc_id)?.align - 1);
@@ -361,22 +367,25 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
         bin_op: mir::BinOp,
         left: PrimVal,
         left_kind: PrimValKind,
-        right: PrimVal,
+        mut right: PrimVal,
         mut right_kind: PrimValKind,
     ) -> EvalResult<'tcx, (PrimVal, PrimVal)> {
+        // The shift amount as it was given, for the overflow check.
+        let (amount, amount_kind) = (right, right_kind);
 
         // These ops can have an RHS with a different numeric type.
         if bin_op == mir::BinOp::Shl || bin_op == mir::BinOp::Shr {
             match (left, right) {
                 (PrimVal::Abstract(abytes), PrimVal::Bytes(rn)) if rn % 8 == 0 => {
                     let num_bytes = (rn / 8) as usize;
+                    let overflow = PrimVal::from_bool(rn >= (left_kind.num_bytes() * 8) as u128);
                     match bin_op {
                         mir::BinOp::Shl => {
                             let mut buffer = [SByte::Concrete(0); 8];
                             for idx in num_bytes .. 8 {
                                 buffer[idx] = abytes[idx - num_bytes];
                             }
-                            return Ok((PrimVal::Abstract(buffer), PrimVal::from_bool(false)));
+                            return Ok((PrimVal::Abstract(buffer), overflow));
                         }
                         mir::BinOp::Shr => {
                             if !left_kind.is_signed_int() {
@@ -384,31 +393,26 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                                 for idx in num_bytes .. 8 {
                                     buffer[idx - num_bytes] = abytes[idx];
                                 }
-                                return Ok((PrimVal::Abstract(buffer), PrimVal::from_bool(false)));
+                                return Ok((PrimVal::Abstract(buffer), overflow));
                             }
                         }
-                        _ => unimplemented!(),
+                        _ => unreachable!(),
                     }
                 }
                 _ => (),
             }
 
-            if right_kind.num_bytes() == left_kind.num_bytes() {
-                right_kind = left_kind;
-            } else if right_kind.num_bytes() < left_kind.num_bytes() {
-                right_kind = left_kind;
-            } else {
-                if let PrimVal::Bytes(n) = right {
-                    if n < 256 {
-                        // HACK
-                        right_kind = left_kind;
-                    } else {
-                        unimplemented!()
-                    }
-                } else {
-                    unimplemented!()
+            if right_kind.num_bytes() > left_kind.num_bytes() {
+                // Unless the shift overflows, which is checked against `amount` below, the
+                // amount is less than the width of `left` and fits into as many bytes.
+                if let PrimVal::Abstract(sbytes) = right {
+                    let len = left_kind.num_bytes();
+                    let mut truncated = [SByte::Concrete(0); 8];
+                    truncated[..len].copy_from_slice(&sbytes[..len]);
+                    right = PrimVal::Abstract(truncated);
                 }
             }
+            right_kind = left_kind;
         }
 
         if left_kind != right_kind {
@@ -419,10 +423,10 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
         use value::PrimValKind::*;
         match bin_op {
             mir::BinOp::Add => {
-                let res = self.memory.constraints.add_binop_constraint(bin_op, left, right, left_kind);
+                let res = self.memory.constraints.add_binop_constraint(bin_op, left, right, left_kind)?;
                 let overflow = match left_kind {
                     U8 | U16 | U32 | U64 | U128 => {
-                        self.memory.constraints.add_binop_constraint(mir::BinOp::Lt, res, left, left_kind)
+                        self.memory.constraints.add_binop_constraint(mir::BinOp::Lt, res, left, left_kind)?
                     }
                     I8 | I16 | I32 | I64 | I128 => {
                         // The basic idea is
@@ -448,13 +452,13 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                         // }
                         // ```
                         let zero = PrimVal::from_i128(0);
-                        let left_positive = self.memory.constraints.add_binop_constraint(mir::BinOp::Ge, left, zero, left_kind);
-                        let right_positive = self.memory.constraints.add_binop_constraint(mir::BinOp::Ge, right, zero, right_kind);
-                        let res_positive = self.memory.constraints.add_binop_constraint(mir::BinOp::Ge, res, zero, left_kind);
-                        let res_negative = self.memory.constraints.add_unop_constraint(mir::UnOp::Not, res_positive, Bool);
-                        let left_positive_then = self.memory.constraints.add_if_then_else(right_positive, Bool, res_negative, PrimVal::from_bool(false));
-                        let left_positive_else = self.memory.constraints.add_if_then_else(right_positive, Bool, PrimVal::from_bool(false), res_positive);
-                        self.memory.constraints.add_if_then_else(left_positive, Bool, left_positive_then, left_positive_else)
+                        let left_positive = self.memory.constraints.add_binop_constraint(mir::BinOp::Ge, left, zero, left_kind)?;
+                        let right_positive = self.memory.constraints.add_binop_constraint(mir::BinOp::Ge, right, zero, right_kind)?;
+                        let res_positive = self.memory.constraints.add_binop_constraint(mir::BinOp::Ge, res, zero, left_kind)?;
+                        let res_negative = self.memory.constraints.add_unop_constraint(mir::UnOp::Not, res_positive, Bool)?;
+                        let left_positive_then = self.memory.constraints.add_if_then_else(right_positive, Bool, res_negative, PrimVal::from_bool(false))?;
+                        let left_positive_else = self.memory.constraints.add_if_then_else(right_positive, Bool, PrimVal::from_bool(false), res_positive)?;
+                        self.memory.constraints.add_if_then_else(left_positive, Bool, left_positive_then, left_positive_else)?
                     }
                     F32 | F64 => PrimVal::from_bool(false),
                     Bool | Char | Ptr | FnPtr => unreachable!(),
@@ -462,30 +466,78 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                 Ok((res, overflow))
             }
             mir::BinOp::Sub => {
-                let res = self.memory.constraints.add_binop_constraint(bin_op, left, right, left_kind);
+                let res = self.memory.constraints.add_binop_constraint(bin_op, left, right, left_kind)?;
                 let overflow = match left_kind {
                     U8 | U16 | U32 | U64 | U128 => {
-                        self.memory.constraints.add_binop_constraint(mir::BinOp::Gt, res, left, left_kind)
+                        self.memory.constraints.add_binop_constraint(mir::BinOp::Gt, res, left, left_kind)?
                     }
                     I8 | I16 | I32 | I64 | I128 => {
                         // same idea as for Add, but we first negate right
                         // negation can lead to overflow, so we replace the bool right_positive
                         // with right_negative
                         let zero = PrimVal::from_i128(0);
-                        let left_positive = self.memory.constraints.add_binop_constraint(mir::BinOp::Ge, left, zero, left_kind);
-                        let right_negative = self.memory.constraints.add_binop_constraint(mir::BinOp::Lt, right, zero, right_kind);
-                        let res_positive = self.memory.constraints.add_binop_constraint(mir::BinOp::Ge, res, zero, left_kind);
-                        let res_negative = self.memory.constraints.add_unop_constraint(mir::UnOp::Not, res_positive, Bool);
-                        let left_positive_then = self.memory.constraints.add_if_then_else(right_negative, Bool, res_negative, PrimVal::from_bool(false));
-                        let left_positive_else = self.memory.constraints.add_if_then_else(right_negative, Bool, PrimVal::from_bool(false), res_positive);
-                        self.memory.constraints.add_if_then_else(left_positive, Bool, left_positive_then, left_positive_else)
+                        let left_positive = self.memory.constraints.add_binop_constraint(mir::BinOp::Ge, left, zero, left_kind)?;
+                        let right_negative = self.memory.constraints.add_binop_constraint(mir::BinOp::Lt, right, zero, right_kind)?;
+                        let res_positive = self.memory.constraints.add_binop_constraint(mir::BinOp::Ge, res, zero, left_kind)?;
+                        let res_negative = self.memory.constraints.add_unop_constraint(mir::UnOp::Not, res_positive, Bool)?;
+                        let left_positive_then = self.memory.constraints.add_if_then_else(right_negative, Bool, res_negative, PrimVal::from_bool(false))?;
+                        let left_positive_else = self.memory.constraints.add_if_then_else(right_negative, Bool, PrimVal::from_bool(false), res_positive)?;
+                        self.memory.constraints.add_if_then_else(left_positive, Bool, left_positive_then, left_positive_else)?
                     }
                     F32 | F64 => PrimVal::from_bool(false),
                     Bool | Char | Ptr | FnPtr => unreachable!(),
                 };
                 Ok((res, overflow))
             }
-            _ => Ok((self.memory.constraints.add_binop_constraint(bin_op, left, right, left_kind), PrimVal::from_bool(false)))
+            mir::BinOp::Mul => {
+                let res = self.memory.constraints.add_binop_constraint(bin_op, left, right, left_kind)?;
+                let overflow = match left_kind {
+                    U8 | U16 | U32 | U64 | U128 | I8 | I16 | I32 | I64 | I128 => {
+                        // The product overflowed iff dividing it by a nonzero `right` does not
+                        // give back `left`:
+                        // ```
+                        // right != 0 && res / right != left
+                        // ```
+                        // For signed integers, `MIN * -1` wraps to `MIN`, and so does `MIN / -1`.
+                        // That case has to be checked separately.
+                        let zero = PrimVal::from_u128(0);
+                        let right_nonzero = self.memory.constraints.add_binop_constraint(mir::BinOp::Ne, right, zero, left_kind)?;
+                        let quotient = self.memory.constraints.add_binop_constraint(mir::BinOp::Div, res, right, left_kind)?;
+                        let quotient_differs = self.memory.constraints.add_binop_constraint(mir::BinOp::Ne, quotient, left, left_kind)?;
+                        let overflow = self.memory.constraints.add_binop_constraint(mir::BinOp::BitAnd, right_nonzero, quotient_differs, Bool)?;
+                        if left_kind.is_signed_int() {
+                            let bits = left_kind.num_bytes() as u32 * 8;
+                            let minus_one = PrimVal::Bytes(u128::max_value() >> (128 - bits));
+                            let min = PrimVal::Bytes(1 << (bits - 1));
+                            let right_minus_one = self.memory.constraints.add_binop_constraint(mir::BinOp::Eq, right, minus_one, left_kind)?;
+                            let left_min = self.memory.constraints.add_binop_constraint(mir::BinOp::Eq, left, min, left_kind)?;
+                            let min_times_minus_one = self.memory.constraints.add_binop_constraint(mir::BinOp::BitAnd, right_minus_one, left_min, Bool)?;
+                            self.memory.constraints.add_binop_constraint(mir::BinOp::BitOr, overflow, min_times_minus_one, Bool)?
+                        } else {
+                            overflow
+                        }
+                    }
+                    F32 | F64 => PrimVal::from_bool(false),
+                    Bool | Char | Ptr | FnPtr => unreachable!(),
+                };
+                Ok((res, overflow))
+            }
+            mir::BinOp::Shl | mir::BinOp::Shr => {
+                let res = self.memory.constraints.add_binop_constraint(bin_op, left, right, left_kind)?;
+                // Shifting by the bit width or more overflows, whatever the signedness of `amount`.
+                let bits = left_kind.num_bytes() as u128 * 8;
+                let overflow = match amount {
+                    PrimVal::Bytes(n) => PrimVal::from_bool(n >= bits),
+                    _ => {
+                        // `amount` has a width of its own, which can differ from that of `left`.
+                        let unsigned_kind = unsigned_kind_of_size(amount_kind);
+                        self.memory.constraints.add_binop_constraint(
+                            mir::BinOp::Ge, amount, PrimVal::Bytes(bits), unsigned_kind)?
+                    }
+                };
+                Ok((res, overflow))
+            }
+            _ => Ok((self.memory.constraints.add_binop_constraint(bin_op, left, right, left_kind)?, PrimVal::from_bool(false)))
         }
     }
 
@@ -500,14 +552,18 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
         use rustc::mir::BinOp::*;
         use value::PrimValKind::*;
         if left.alloc_id != right.alloc_id {
-            if let Eq = bin_op {
-                unimplemented!()
-            } else {
-                unimplemented!()
+            // Pointers into different allocations are never equal.
+            match bin_op {
+                Eq => Ok((PrimVal::from_bool(false), PrimVal::from_bool(false))),
+                Ne => Ok((PrimVal::from_bool(true), PrimVal::from_bool(false))),
+                _ => {
+                    let msg = format!("unimplemented binary op on pointers into different allocations {:?}: {:?}, {:?}", bin_op, left, right);
+                    Err(EvalError::Unimplemented(msg))
+                }
             }
         } else {
             let result = self.memory.constraints.add_binop_constraint(
-                bin_op, left.offset.as_primval(), right.offset.as_primval(), U64);
+                bin_op, left.offset.as_primval(), right.offset.as_primval(), U64)?;
             Ok((result, PrimVal::from_bool(false)))
         }
     }
@@ -523,8 +579,8 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
 
         if !val.is_concrete() {
             return
-                Ok(self.memory.constraints.add_unop_constraint(
-                    un_op, val, val_kind))
+                self.memory.constraints.add_unop_constraint(
+                    un_op, val, val_kind)
         }
 
         let bytes = val.to_bytes()?;
@@ -562,3 +618,16 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
         Ok(PrimVal::Bytes(result_bytes))
     }
 }
+
+/// The unsigned integer kind with the same width as `kind`.
+fn unsigned_kind_of_size(kind: PrimValKind) -> PrimValKind {
+    use value::PrimValKind::*;
+    match kind {
+        U8 | I8 => U8,
+        U16 | I16 => U16,
+        U32 | I32 => U32,
+        U64 | I64 => U64,
+        U128 | I128 => U128,
+        _ => bug!("no unsigned integer kind of the same size as {:?}", kind),
+    }
+}
@@ -46,7 +46,7 @@ pub struct GlobalId<'tcx> {
     pub(super) promoted: Option<mir::Promoted>,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Global<'tcx> {
     pub(super) value: Value,
     /// Only used in `force_allocation` to ensure we don't mark the memory
@@ -86,16 +86,17 @@ impl<'tcx> Place<'tcx> {
         ptr.to_ptr()
     }
 
-    pub(super) fn elem_ty_and_len(self, ty: Ty<'tcx>, tcx: TyCtxt<'_, 'tcx, '_>) -> (Ty<'tcx>, u64) {
+    pub(super) fn elem_ty_and_len(self, ty: Ty<'tcx>, tcx: TyCtxt<'_, 'tcx, '_>) -> EvalResult<'tcx, (Ty<'tcx>, u64)> {
         match ty.sty {
-            ty::TyArray(elem, n) => (elem, n.unwrap_usize(tcx)),
+            ty::TyArray(elem, n) => Ok((elem, n.unwrap_usize(tcx))),
 
             ty::TySlice(elem) => {
                 match self {
                     Place::Ptr { extra: PlaceExtra::Length(len), .. } => {
                         match len {
-                            PrimVal::Bytes(n) => (elem, n as u64),
-                            _ => unimplemented!(),
+                            PrimVal::Bytes(n) => Ok((elem, n as u64)),
+                            _ => Err(EvalError::Unimplemented(
+                                format!("indexing a slice of input-dependent length {:?}", len))),
                         }
                     }
                     _ => bug!("elem_ty_and_len of a TySlice given non-slice place: {:?}", self),
@@ -369,7 +370,7 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
         let base = self.force_allocation(base)?;
         let (base_ptr, _) = base.to_ptr_and_extra();
 
-        let (elem_ty, len) = base.elem_ty_and_len(outer_ty, self.tcx);
+        let (elem_ty, len) = base.elem_ty_and_len(outer_ty, self.tcx)?;
         let elem_size = self.type_size(elem_ty)?.expect(
             "slice element must be sized",
         );
@@ -399,13 +400,13 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                     mir::BinOp::Mul,
                     idx,
                     PrimVal::Bytes(elem_size as u128),
-                    PrimValKind::U64);
+                    PrimValKind::U64)?;
 
                 let offset = self.memory.constraints.add_binop_constraint(
                     mir::BinOp::Add,
                     p.offset.as_primval(),
                     byte_index,
-                    PrimValKind::U64);
+                    PrimValKind::U64)?;
                 PrimVal::Ptr(MemoryPointer::with_primval_offset(p.alloc_id, offset))
             };
 
@@ -476,7 +477,7 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                 let base = self.force_allocation(base)?;
                 let (base_ptr, _) = base.to_ptr_and_extra();
 
-                let (elem_ty, n) = base.elem_ty_and_len(base_ty, self.tcx);
+                let (elem_ty, n) = base.elem_ty_and_len(base_ty, self.tcx)?;
                 let elem_size = self.type_size(elem_ty)?.expect(
                     "sequence element must be sized",
                 );
@@ -497,7 +498,7 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                 let base = self.force_allocation(base)?;
                 let (base_ptr, _) = base.to_ptr_and_extra();
 
-                let (elem_ty, n) = base.elem_ty_and_len(base_ty, self.tcx);
+                let (elem_ty, n) = base.elem_ty_and_len(base_ty, self.tcx)?;
                 let elem_size = self.type_size(elem_ty)?.expect(
                     "slice element must be sized",
                 );
@@ -0,0 +1,170 @@
+//! Generation of Rust regression tests for the panics seer finds.
+//!
+//! Every test runs the compiled binary on the stdin, symbolic arguments and symbolic
+//! environment variables seer solved for, with the values of the `mksym` variables in
+//! `SEER_HELPER_REPLAY` for the helper crate to write back, and checks that the binary still
+//! panics with the same message.
+
+use std::fmt::Write as FmtWrite;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use rustc::mir;
+
+use constraints::SatisfiedVar;
+use error::StaticEvalError;
+use executor::ExecutionComplete;
+
+pub(crate) struct RegressionTests {
+    path: PathBuf,
+    binary: String,
+    written: usize,
+}
+
+impl RegressionTests {
+    pub fn new(path: PathBuf, binary: String) -> Self {
+        RegressionTests { path, binary, written: 0 }
+    }
+
+    /// Appends a test for `complete` to the file, if the path ended in an error that the
+    /// compiled binary reports as a panic. The file is overwritten by the first test.
+    pub fn add(&mut self, complete: &ExecutionComplete) -> io::Result<()> {
+        let expected = match complete.result {
+            Err(ref e) => match expected_panic(e) {
+                Some(expected) => expected,
+                None => return Ok(()),
+            },
+            Ok(()) => return Ok(()),
+        };
+        if self.written == 0 {
+            fs::write(&self.path, header(&self.binary))?;
+        }
+        let test = test_fn(self.written, &complete.input, &expected);
+        OpenOptions::new().append(true).open(&self.path)?.write_all(test.as_bytes())?;
+        self.written += 1;
+        Ok(())
+    }
+}
+
+/// What a debug build of the program prints when it runs into `e`, if that is a panic.
+fn expected_panic(e: &StaticEvalError) -> Option<String> {
+    let msg = match *e {
+        // A message with input-dependent parts cannot be matched literally.
+        StaticEvalError::Panic { message: Some(ref message), .. } if !message.contains("{..}") => {
+            return Some(message.clone());
+        }
+        StaticEvalError::Panic { .. } => "panicked at",
+        StaticEvalError::Overflow(op) => match op {
+            mir::BinOp::Add => "attempt to add with overflow",
+            mir::BinOp::Sub => "attempt to subtract with overflow",
+            mir::BinOp::Mul => "attempt to multiply with overflow",
+            mir::BinOp::Div => "attempt to divide with overflow",
+            mir::BinOp::Rem => "attempt to calculate the remainder with overflow",
+            mir::BinOp::Shl => "attempt to shift left with overflow",
+            mir::BinOp::Shr => "attempt to shift right with overflow",
+            _ => "with overflow",
+        },
+        StaticEvalError::OverflowNeg => "attempt to negate with overflow",
+        StaticEvalError::DivisionByZero => "attempt to divide by zero",
+        StaticEvalError::RemainderByZero =>
+            "attempt to calculate the remainder with a divisor of zero",
+        StaticEvalError::ArrayIndexOutOfBounds(..) => "index out of bounds",
+        _ => return None,
+    };
+    Some(msg.to_string())
+}
+
+fn header(binary: &str) -> String {
+    format!(r#"// Regression tests generated by seer for `{binary}`. Each test runs the binary on an
+// input that made it fail and checks that it still fails the same way.
+
+use std::io::Write;
+use std::process::{{Command, Stdio}};
+
+/// Runs `{binary}` with the given arguments, environment variables, stdin and `mksym` values.
+/// A variable without a value is removed from the environment. Returns whether the binary
+/// exited successfully, and what it printed to stderr.
+fn seer_run(args: &[&str], env: &[(&str, Option<&str>)], stdin: &[u8], replay: &str)
+            -> (bool, String) {{
+    let mut path = ::std::env::current_exe().unwrap();
+    path.pop();
+    if path.ends_with("deps") {{
+        path.pop();
+    }}
+    path.push("{binary}");
+    let mut command = Command::new(&path);
+    command.args(args);
+    for &(name, value) in env {{
+        match value {{
+            Some(value) => command.env(name, value),
+            None => command.env_remove(name),
+        }};
+    }}
+    let mut child = command
+        .env("SEER_HELPER_REPLAY", replay)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap_or_else(|e| panic!("could not run {{}}: {{}}", path.display(), e));
+    // The binary may fail before it has read all of its input.
+    let _ = child.stdin.take().unwrap().write_all(stdin);
+    let output = child.wait_with_output().unwrap();
+    (output.status.success(), String::from_utf8_lossy(&output.stderr).into_owned())
+}}
+"#, binary = binary)
+}
+
+fn test_fn(idx: usize, input: &[SatisfiedVar], expected: &str) -> String {
+    let mut stdin: &[u8] = &[];
+    let mut args = Vec::new();
+    let mut env = Vec::new();
+    let mut replay = Vec::new();
+    let mut comments = String::new();
+    for var in input {
+        if var.label == "stdin" {
+            stdin = &var.assignments;
+            continue;
+        }
+        if var.label.starts_with("argv[") {
+            // Symbolic arguments are ASCII, so the string is exactly what was solved for.
+            args.push(var.assignments_str.clone()
+                .unwrap_or_else(|| String::from_utf8_lossy(&var.assignments).into_owned()));
+            continue;
+        }
+        if var.label.starts_with("env[") && var.label.ends_with(']') {
+            let name = var.label["env[".len()..var.label.len() - 1].to_string();
+            env.push((name, var.assignments_str.clone()));
+            continue;
+        }
+        let value = match var.assignments_str {
+            Some(ref s) => s.clone(),
+            None => format!("{:?}", var.assignments),
+        };
+        writeln!(comments, "    // {} = {}", one_line(&var.label), one_line(&value)).unwrap();
+        let hex: Vec<String> = var.assignments.iter().map(|b| format!("{:02x}", b)).collect();
+        replay.push(hex.concat());
+    }
+
+    format!(r#"
+#[test]
+fn seer_regression_{idx}() {{
+{comments}    let (success, stderr) = seer_run(&{args:?}, &{env:?}, &{stdin:?}, {replay:?});
+    assert!(!success);
+    assert!(stderr.contains({expected:?}), "unexpected stderr: {{}}", stderr);
+}}
+"#,
+            idx = idx,
+            comments = comments,
+            args = args,
+            env = env,
+            stdin = stdin,
+            replay = replay.join(","),
+            expected = expected)
+}
+
+/// `s` with line breaks replaced by spaces, so that it fits in a line comment.
+fn one_line(s: &str) -> String {
+    s.replace(|c: char| c == '\n' || c == '\r', " ")
+}
@@ -0,0 +1,282 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+use std::rc::Rc;
+
+use coverage::Coverage;
+use eval_context::EvalContext;
+
+/// Selects which of the pending execution states is stepped next.
+///
+/// The strategy owns every `EvalContext` that has not yet finished. `Executor::run` pops a
+/// state, steps it once and pushes back whatever states result from that step.
+pub trait SearchStrategy<'a, 'tcx: 'a> {
+    fn push(&mut self, ecx: EvalContext<'a, 'tcx>);
+
+    fn pop(&mut self) -> Option<EvalContext<'a, 'tcx>>;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchStrategyKind {
+    /// Always continue with the most recently pushed state.
+    DepthFirst,
+
+    /// Always continue with the least recently pushed state.
+    BreadthFirst,
+
+    /// Continue with a pending state chosen uniformly at random. The same seed
+    /// always yields the same exploration order.
+    RandomPath { seed: u64 },
+
+    /// Continue with the state that has executed the fewest steps so far.
+    ShortestPathFirst,
+
+    /// Continue with the state closest to a basic block that no path has executed yet.
+    CoverageGuided,
+}
+
+impl Default for SearchStrategyKind {
+    fn default() -> Self {
+        SearchStrategyKind::DepthFirst
+    }
+}
+
+impl SearchStrategyKind {
+    pub(crate) fn build<'a, 'tcx: 'a>(
+        self,
+        coverage: Rc<RefCell<Coverage<'tcx>>>,
+    ) -> Box<SearchStrategy<'a, 'tcx> + 'a> {
+        match self {
+            SearchStrategyKind::DepthFirst => Box::new(DepthFirst::new()),
+            SearchStrategyKind::BreadthFirst => Box::new(BreadthFirst::new()),
+            SearchStrategyKind::RandomPath { seed } => Box::new(RandomPath::new(seed)),
+            SearchStrategyKind::ShortestPathFirst => Box::new(ShortestPathFirst::new()),
+            SearchStrategyKind::CoverageGuided => Box::new(CoverageGuided::new(coverage)),
+        }
+    }
+}
+
+pub struct DepthFirst<'a, 'tcx: 'a> {
+    stack: Vec<EvalContext<'a, 'tcx>>,
+}
+
+impl <'a, 'tcx: 'a> DepthFirst<'a, 'tcx> {
+    pub fn new() -> Self {
+        DepthFirst { stack: Vec::new() }
+    }
+}
+
+impl <'a, 'tcx: 'a> SearchStrategy<'a, 'tcx> for DepthFirst<'a, 'tcx> {
+    fn push(&mut self, ecx: EvalContext<'a, 'tcx>) {
+        self.stack.push(ecx);
+    }
+
+    fn pop(&mut self) -> Option<EvalContext<'a, 'tcx>> {
+        self.stack.pop()
+    }
+
+    fn len(&self) -> usize {
+        self.stack.len()
+    }
+}
+
+pub struct BreadthFirst<'a, 'tcx: 'a> {
+    queue: VecDeque<EvalContext<'a, 'tcx>>,
+}
+
+impl <'a, 'tcx: 'a> BreadthFirst<'a, 'tcx> {
+    pub fn new() -> Self {
+        BreadthFirst { queue: VecDeque::new() }
+    }
+}
+
+impl <'a, 'tcx: 'a> SearchStrategy<'a, 'tcx> for BreadthFirst<'a, 'tcx> {
+    fn push(&mut self, ecx: EvalContext<'a, 'tcx>) {
+        self.queue.push_back(ecx);
+    }
+
+    fn pop(&mut self) -> Option<EvalContext<'a, 'tcx>> {
+        self.queue.pop_front()
+    }
+
+    fn len(&self) -> usize {
+        self.queue.len()
+    }
+}
+
+pub struct RandomPath<'a, 'tcx: 'a> {
+    states: Vec<EvalContext<'a, 'tcx>>,
+    rng: XorShift,
+}
+
+impl <'a, 'tcx: 'a> RandomPath<'a, 'tcx> {
+    pub fn new(seed: u64) -> Self {
+        RandomPath {
+            states: Vec::new(),
+            rng: XorShift::new(seed),
+        }
+    }
+}
+
+impl <'a, 'tcx: 'a> SearchStrategy<'a, 'tcx> for RandomPath<'a, 'tcx> {
+    fn push(&mut self, ecx: EvalContext<'a, 'tcx>) {
+        self.states.push(ecx);
+    }
+
+    fn pop(&mut self) -> Option<EvalContext<'a, 'tcx>> {
+        if self.states.is_empty() {
+            return None;
+        }
+        let idx = (self.rng.next() % self.states.len() as u64) as usize;
+        Some(self.states.swap_remove(idx))
+    }
+
+    fn len(&self) -> usize {
+        self.states.len()
+    }
+}
+
+/// xorshift64*. Good enough to pick states, and keeps runs reproducible without
+/// pulling in another dependency.
+struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        // The all-zero state is a fixed point, so it must be avoided.
+        XorShift { state: if seed == 0 { 0x9e37_79b9_7f4a_7c15 } else { seed } }
+    }
+
+    fn next(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+pub struct ShortestPathFirst<'a, 'tcx: 'a> {
+    heap: BinaryHeap<ByStepsTaken<'a, 'tcx>>,
+    next_seq: u64,
+}
+
+impl <'a, 'tcx: 'a> ShortestPathFirst<'a, 'tcx> {
+    pub fn new() -> Self {
+        ShortestPathFirst {
+            heap: BinaryHeap::new(),
+            next_seq: 0,
+        }
+    }
+}
+
+impl <'a, 'tcx: 'a> SearchStrategy<'a, 'tcx> for ShortestPathFirst<'a, 'tcx> {
+    fn push(&mut self, ecx: EvalContext<'a, 'tcx>) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.heap.push(ByStepsTaken { ecx, seq });
+    }
+
+    fn pop(&mut self) -> Option<EvalContext<'a, 'tcx>> {
+        self.heap.pop().map(|entry| entry.ecx)
+    }
+
+    fn len(&self) -> usize {
+        self.heap.len()
+    }
+}
+
+/// Orders states so that the max-heap yields the one with the most steps remaining, i.e. the
+/// fewest steps taken. Ties go to the state that was pushed first.
+struct ByStepsTaken<'a, 'tcx: 'a> {
+    ecx: EvalContext<'a, 'tcx>,
+    seq: u64,
+}
+
+impl <'a, 'tcx: 'a> PartialEq for ByStepsTaken<'a, 'tcx> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl <'a, 'tcx: 'a> Eq for ByStepsTaken<'a, 'tcx> {}
+
+impl <'a, 'tcx: 'a> PartialOrd for ByStepsTaken<'a, 'tcx> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl <'a, 'tcx: 'a> Ord for ByStepsTaken<'a, 'tcx> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.ecx.steps_remaining.cmp(&other.ecx.steps_remaining)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+pub(crate) struct CoverageGuided<'a, 'tcx: 'a> {
+    states: Vec<EvalContext<'a, 'tcx>>,
+    coverage: Rc<RefCell<Coverage<'tcx>>>,
+}
+
+/// How many control-flow edges returning from a call is worth when comparing states.
+const CALLER_PENALTY: u32 = 16;
+
+impl <'a, 'tcx: 'a> CoverageGuided<'a, 'tcx> {
+    pub fn new(coverage: Rc<RefCell<Coverage<'tcx>>>) -> Self {
+        CoverageGuided {
+            states: Vec::new(),
+            coverage,
+        }
+    }
+
+    /// Lower is better. Looks for uncovered blocks in the current function first and then in
+    /// each caller, penalizing every frame that has to return before the block is reached.
+    fn score(&self, ecx: &EvalContext<'a, 'tcx>) -> u32 {
+        let coverage = self.coverage.borrow();
+        let mut best = u32::max_value();
+        for (depth, frame) in ecx.stack().iter().rev().enumerate() {
+            let penalty = (depth as u32).saturating_mul(CALLER_PENALTY);
+            if penalty >= best {
+                break;
+            }
+            if let Some(distance) = coverage.distance_to_uncovered(frame.instance, frame.block) {
+                best = ::std::cmp::min(best, penalty.saturating_add(distance));
+            }
+        }
+        best
+    }
+}
+
+impl <'a, 'tcx: 'a> SearchStrategy<'a, 'tcx> for CoverageGuided<'a, 'tcx> {
+    fn push(&mut self, ecx: EvalContext<'a, 'tcx>) {
+        self.states.push(ecx);
+    }
+
+    fn pop(&mut self) -> Option<EvalContext<'a, 'tcx>> {
+        let mut best: Option<(usize, u32)> = None;
+        // Iterate newest first so that ties keep going depth-first.
+        for (idx, ecx) in self.states.iter().enumerate().rev() {
+            let score = self.score(ecx);
+            if best.map_or(true, |(_, best_score)| score < best_score) {
+                best = Some((idx, score));
+                if score == 0 {
+                    break;
+                }
+            }
+        }
+        best.map(|(idx, _)| self.states.remove(idx))
+    }
+
+    fn len(&self) -> usize {
+        self.states.len()
+    }
+}
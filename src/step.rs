@@ -9,10 +9,12 @@ use rustc::mir;
 use rustc::ty::{subst, self};
 use rustc::mir::interpret::ConstValue;
 
+use constraints::Constraint;
 use error::{EvalResult, EvalError};
 use eval_context::{EvalContext, StackPopCleanup};
-use executor::FinishStep;
+use executor::{FinishStep, FinishStepVariant};
 use place::{Global, GlobalId, Place};
+use value::{PrimVal, PrimValKind};
 use syntax::codemap::Span;
 
 impl<'a, 'tcx> EvalContext<'a, 'tcx> {
@@ -57,7 +59,14 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                 new_constants: &mut new,
             }.visit_statement(block, stmt, mir::Location { block, statement_index: stmt_id });
             if new? == 0 {
-                self.statement(stmt)?;
+                match self.statement(stmt) {
+                    Err(EvalError::NeedsConcreteValue { value, kind, candidates, otherwise }) => {
+                        let branches = self.concretization_branches(
+                            value, kind, candidates, *otherwise);
+                        return Ok((true, Some(branches)));
+                    }
+                    result => result?,
+                }
             }
             // if ConstantExtractor added new frames, we don't execute anything here
             // but await the next call to step
@@ -130,12 +139,50 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                   -> EvalResult<'tcx, Option<Vec<FinishStep<'tcx>>>>
     {
         trace!("{:?}", terminator.kind);
-        let result = self.eval_terminator(terminator)?;
+        let result = match self.eval_terminator(terminator) {
+            Err(EvalError::NeedsConcreteValue { value, kind, candidates, otherwise }) => {
+                Some(self.concretization_branches(value, kind, candidates, *otherwise))
+            }
+            result => result?,
+        };
         if !self.stack.is_empty() {
             trace!("// {:?}", self.frame().block);
         }
         Ok(result)
     }
+
+    /// Forks the current step over the feasible `candidates` for `value`, taking the step
+    /// again once the value is pinned down. If `value` can be none of them, that path ends in
+    /// `otherwise`.
+    fn concretization_branches(
+        &self,
+        value: PrimVal,
+        kind: PrimValKind,
+        candidates: Vec<u128>,
+        otherwise: EvalError<'tcx>,
+    ) -> Vec<FinishStep<'tcx>> {
+        let mut branches = Vec::new();
+        let mut none_of_them = Vec::new();
+        for candidate in candidates {
+            let candidate = PrimVal::Bytes(candidate);
+            let equal = vec![Constraint::new_compare(mir::BinOp::Eq, kind, value, candidate)];
+            if self.memory.constraints.is_feasible_with(&equal) {
+                branches.push(FinishStep {
+                    constraints: equal,
+                    variant: FinishStepVariant::Retry,
+                });
+            }
+            none_of_them.push(Constraint::new_compare(mir::BinOp::Ne, kind, value, candidate));
+        }
+
+        if self.memory.constraints.is_feasible_with(&none_of_them) {
+            branches.push(FinishStep {
+                constraints: none_of_them,
+                variant: FinishStepVariant::Error(otherwise),
+            });
+        }
+        branches
+    }
 }
 
 // WARNING: make sure that any methods implemented on this type don't ever access ecx.stack
@@ -13,8 +13,12 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
         trace!("drop_place: {:#?}", lval);
 
         // FIXME: Surely there is a more robust  way to check for this case?
-        if format!("{:?}", ty) == "std::io::Stdin" {
-            return Ok(())
+        match &format!("{:?}", ty)[..] {
+            // `Args` holds only the index of the next argument, see `terminator::env`.
+            "std::io::Stdin" | "std::env::Args" => return Ok(()),
+            // A `StdinLock` holds nothing, see `terminator::stdin`.
+            name if name.starts_with("std::io::StdinLock") => return Ok(()),
+            _ => {}
         }
 
         let val = match self.force_allocation(lval)? {
@@ -0,0 +1,328 @@
+//! A model of the environment of the program.
+//!
+//! `std::env::args` yields the program name first, and every other argument is a symbolic
+//! string of bounded length, labelled `argv[1]`, `argv[2]` and so on. An `Args` value holds
+//! nothing but the index of the next argument, in its first word. The arguments themselves live
+//! in `EvalContext::program_args`.
+//!
+//! Environment variables declared as symbolic may or may not be set, and have a symbolic value
+//! of bounded length if they are. Each is labelled `env[NAME]`. Reading one forks the path on
+//! whether it is set. Every other variable is unset.
+
+use rustc::mir;
+use rustc::ty::{self, Ty};
+use rustc::ty::layout::LayoutOf;
+use rustc::ty::subst::Kind;
+
+use error::{EvalError, EvalResult};
+use eval_context::{EvalContext, SymbolicEnvVar, SymbolicString};
+use format_executor::field_ty_and_offset;
+use memory::{MemoryPointer, SByte};
+use place::Place;
+use value::{PrimVal, PrimValKind, Value};
+
+impl<'a, 'tcx> EvalContext<'a, 'tcx> {
+    /// Sets up the arguments: `program_name`, followed by `count` symbolic ones of at most
+    /// `max_len` bytes each.
+    pub(crate) fn init_program_args(
+        &mut self,
+        program_name: &str,
+        count: usize,
+        max_len: u64,
+    ) -> EvalResult<'tcx> {
+        self.program_args = vec![SymbolicString {
+            bytes: program_name.bytes().map(SByte::Concrete).collect(),
+            len: PrimVal::Bytes(program_name.len() as u128),
+        }];
+
+        for idx in 1..count + 1 {
+            let label = format!("argv[{}]", idx);
+            let bytes = self.memory.constraints.fresh_symbolic_string(label, max_len as u32);
+            let arg = self.symbolic_string(bytes)?;
+            self.program_args.push(arg);
+        }
+        Ok(())
+    }
+
+    /// Declares the environment variables in `names` as symbolic, with values of at most
+    /// `max_len` bytes. The same bound applies to those declared later by the program.
+    pub(crate) fn init_symbolic_env_vars(
+        &mut self,
+        names: &[String],
+        max_len: u64,
+    ) -> EvalResult<'tcx> {
+        self.symbolic_env_var_len = max_len;
+        for name in names {
+            self.declare_symbolic_env_var(name.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Makes the environment variable `name` symbolic, unless it already is.
+    pub(super) fn declare_symbolic_env_var(&mut self, name: &[u8]) -> EvalResult<'tcx> {
+        if self.symbolic_env_vars.iter().any(|var| var.name == name) {
+            return Ok(());
+        }
+        let label = format!("env[{}]", String::from_utf8_lossy(name));
+        let max_len = self.symbolic_env_var_len as u32;
+        let mut bytes = self.memory.constraints.fresh_symbolic_optional_string(label, max_len);
+        let mut present = [SByte::Concrete(0); 8];
+        present[0] = bytes.remove(0);
+        let value = self.symbolic_string(bytes)?;
+        self.symbolic_env_vars.push(SymbolicEnvVar {
+            name: name.to_vec(),
+            present: PrimVal::Abstract(present),
+            value,
+        });
+        Ok(())
+    }
+
+    /// Evaluates a call to `std::env::_var_os`, which `var` and `var_os` call with the name of
+    /// the variable as an `&OsStr`.
+    pub(super) fn call_var_os(
+        &mut self,
+        destination: Option<(Place<'tcx>, mir::BasicBlock)>,
+        args: &[Value],
+        dest_ty: Ty<'tcx>,
+    ) -> EvalResult<'tcx> {
+        let (dest, block) = destination.expect("std::env::var_os() does not diverge");
+        let name = match args[0] {
+            Value::ByValPair(PrimVal::Ptr(ptr), PrimVal::Bytes(len)) => {
+                self.read_concrete_bytes(ptr, len as u64)?
+            }
+            other => {
+                return Err(EvalError::Unimplemented(
+                    format!("std::env::var_os() of {:?}", other)));
+            }
+        };
+        match self.lookup_env_var(&name)? {
+            Some(value) => {
+                // May fork over the possible lengths, so it has to come first.
+                let len = self.symbolic_string_len(&value)?;
+                let os_string_ty = match dest_ty.sty {
+                    ty::TyAdt(_, substs) => substs.type_at(0),
+                    _ => bug!("std::env::var_os() returns {:?}", dest_ty),
+                };
+                let dest = self.force_allocation(dest)?;
+                self.write_byte_buf(dest.to_ptr()?, os_string_ty, &value.bytes[..len as usize])?;
+                self.write_discriminant_value(dest_ty, dest, 1)?; // Some
+            }
+            None => self.write_discriminant_value(dest_ty, dest, 0)?,
+        }
+        self.goto_block(block);
+        Ok(())
+    }
+
+    /// Evaluates a call to the C function `getenv`. The value of a symbolic variable is copied
+    /// to a new allocation the first time it is read, like `setenv` would, and then stays there.
+    pub(super) fn getenv(&mut self, name_ptr: MemoryPointer) -> EvalResult<'tcx, PrimVal> {
+        let name = self.memory.read_c_str(name_ptr)?;
+        if let Some(&ptr) = self.env_vars.get(&name) {
+            return Ok(PrimVal::Ptr(ptr));
+        }
+        let value = match self.lookup_env_var(&name)? {
+            Some(value) => value,
+            None => return Ok(PrimVal::Bytes(0)),
+        };
+        // The string ends at its first NUL byte, so its length does not need to be known.
+        let len = value.bytes.len() as u64;
+        let ptr = self.memory.allocate(len + 1, 1)?;
+        self.memory.write_sbytes(ptr, &value.bytes)?;
+        self.memory.write_bytes(ptr.offset(len, self.memory.layout)?, &[0])?;
+        // Like the environment of a real process, it is never freed.
+        self.memory.mark_static_initalized(ptr.alloc_id, true)?;
+        self.env_vars.insert(name, ptr);
+        Ok(PrimVal::Ptr(ptr))
+    }
+
+    /// Evaluates a call to `std::env::args`, or to one of the methods of `Args` at `path`.
+    pub(super) fn call_args_fn(
+        &mut self,
+        path: &str,
+        destination: Option<(Place<'tcx>, mir::BasicBlock)>,
+        args: &[Value],
+        dest_ty: Ty<'tcx>,
+    ) -> EvalResult<'tcx> {
+        let (dest, block) = destination.expect("Args functions do not diverge");
+        match path {
+            "std::env::args" => {
+                let dest = self.force_allocation(dest)?.to_ptr()?;
+                self.memory.write_usize(dest, 0)?;
+            }
+            "<std::env::Args as std::iter::Iterator>::next" => {
+                let index_ptr = args[0].read_ptr(&self.memory)?.to_ptr()?;
+                let index = self.memory.read_usize(index_ptr)?;
+                match self.program_args.get(index as usize).cloned() {
+                    Some(arg) => {
+                        // May fork over the possible lengths, so it has to come first.
+                        let len = self.symbolic_string_len(&arg)?;
+                        let string_ty = match dest_ty.sty {
+                            ty::TyAdt(_, substs) => substs.type_at(0),
+                            _ => bug!("Args::next() returns {:?}", dest_ty),
+                        };
+                        let dest = self.force_allocation(dest)?;
+                        self.write_byte_buf(dest.to_ptr()?, string_ty, &arg.bytes[..len as usize])?;
+                        self.write_discriminant_value(dest_ty, dest, 1)?; // Some
+                        self.memory.write_usize(index_ptr, index + 1)?;
+                    }
+                    None => self.write_discriminant_value(dest_ty, dest, 0)?,
+                }
+            }
+            "<std::env::Args as std::iter::Iterator>::size_hint" => {
+                // Both bounds are the number of remaining arguments.
+                let remaining = self.remaining_program_args(args[0])?;
+                let layout = self.layout_of(dest_ty)?;
+                let dest = self.force_allocation(dest)?;
+                let (lower, lower_layout) = self.place_field(dest, mir::Field::new(0), layout)?;
+                self.write_primval(lower, remaining, lower_layout.ty)?;
+
+                let (upper, upper_layout) = self.place_field(dest, mir::Field::new(1), layout)?;
+                self.write_discriminant_value(upper_layout.ty, upper, 1)?; // Some
+                let upper = self.place_downcast(upper, 1)?;
+                let (upper, value_layout) =
+                    self.place_field(upper, mir::Field::new(0), upper_layout)?;
+                self.write_primval(upper, remaining, value_layout.ty)?;
+            }
+            "<std::env::Args as std::iter::ExactSizeIterator>::len" => {
+                let remaining = self.remaining_program_args(args[0])?;
+                let usize = self.tcx.types.usize;
+                self.write_primval(dest, remaining, usize)?;
+            }
+            _ => bug!("{} is not a function of std::env::Args", path),
+        }
+        self.goto_block(block);
+        Ok(())
+    }
+
+    /// The value of the environment variable `name`, if it is set. If that depends on the
+    /// input, asks `step` to fork over both cases.
+    fn lookup_env_var(&self, name: &[u8]) -> EvalResult<'tcx, Option<SymbolicString>> {
+        let var = match self.symbolic_env_vars.iter().find(|var| var.name == name) {
+            Some(var) => var,
+            None => return Ok(None),
+        };
+        let kind = PrimValKind::U8;
+        let present = match self.memory.constraints.unique_value(var.present, kind, 1) {
+            Ok(present) => present,
+            Err(candidates) => return Err(EvalError::NeedsConcreteValue {
+                value: var.present,
+                kind,
+                candidates,
+                // Cannot happen, the variable is either set or not.
+                otherwise: Box::new(EvalError::Unimplemented(
+                    "environment variable neither set nor unset".to_string())),
+            }),
+        };
+        Ok(if present == 1 { Some(var.value.clone()) } else { None })
+    }
+
+    /// A string made of the symbolic `bytes`, which ends at the first NUL byte if there is one.
+    fn symbolic_string(&mut self, bytes: Vec<SByte>) -> EvalResult<'tcx, SymbolicString> {
+        let mut len = PrimVal::Bytes(bytes.len() as u128);
+        for (pos, &byte) in bytes.iter().enumerate().rev() {
+            let mut sbytes = [SByte::Concrete(0); 8];
+            sbytes[0] = byte;
+            let is_nul = self.memory.constraints.add_binop_constraint(
+                mir::BinOp::Eq, PrimVal::Abstract(sbytes), PrimVal::Bytes(0), PrimValKind::U8)?;
+            len = self.memory.constraints.add_if_then_else(
+                is_nul, PrimValKind::U64, PrimVal::Bytes(pos as u128), len)?;
+        }
+        Ok(SymbolicString { bytes, len })
+    }
+
+    /// The length of `string`. If it depends on the input, asks `step` to fork over the
+    /// lengths it can have.
+    pub(super) fn symbolic_string_len(&self, string: &SymbolicString) -> EvalResult<'tcx, u64> {
+        if string.len.is_concrete() {
+            return string.len.to_u64();
+        }
+        let kind = PrimValKind::U64;
+        let max = string.bytes.len() as u128;
+        match self.memory.constraints.unique_value(string.len, kind, max) {
+            Ok(len) => Ok(len as u64),
+            Err(candidates) => Err(EvalError::NeedsConcreteValue {
+                value: string.len,
+                kind,
+                candidates,
+                // Cannot happen, a string is never longer than its bytes.
+                otherwise: Box::new(EvalError::Unimplemented(
+                    "symbolic string longer than its bound".to_string())),
+            }),
+        }
+    }
+
+    /// Reads `len` bytes at `ptr`, all of which have to be concrete.
+    pub(super) fn read_concrete_bytes(
+        &self,
+        ptr: MemoryPointer,
+        len: u64,
+    ) -> EvalResult<'tcx, Vec<u8>> {
+        self.memory.read_bytes(PrimVal::Ptr(ptr), len)?.iter()
+            .map(|b| match *b {
+                SByte::Concrete(b) => Ok(b),
+                SByte::Abstract(_) => Err(EvalError::Unimplemented(
+                    "environment variable with a symbolic name".to_string())),
+            })
+            .collect()
+    }
+
+    /// The number of arguments that the `Args` behind the reference `args` has yet to yield.
+    fn remaining_program_args(&self, args: Value) -> EvalResult<'tcx, PrimVal> {
+        let index_ptr = args.read_ptr(&self.memory)?.to_ptr()?;
+        let index = self.memory.read_usize(index_ptr)?;
+        let remaining = (self.program_args.len() as u64).saturating_sub(index);
+        Ok(PrimVal::Bytes(remaining as u128))
+    }
+
+    /// Writes a `Vec<u8>` holding `bytes` to `dest`, or a `String` or `OsString`, which wrap
+    /// one. The bytes are copied to a new allocation, which the program frees when it drops the
+    /// value.
+    fn write_byte_buf(
+        &mut self,
+        dest: MemoryPointer,
+        ty: Ty<'tcx>,
+        bytes: &[SByte],
+    ) -> EvalResult<'tcx> {
+        let (ptr_offset, cap_offset, len_offset) = self.byte_vec_offsets(ty)?;
+        let len = bytes.len() as u64;
+        let layout = self.memory.layout;
+        let ptr_field = dest.offset(ptr_offset, layout)?;
+        if len == 0 {
+            // An empty `Vec<u8>` holds a dangling pointer, like `NonNull::dangling`.
+            self.memory.write_usize(ptr_field, 1)?;
+        } else {
+            let data = self.memory.allocate(len, 1)?;
+            self.memory.write_sbytes(data, bytes)?;
+            self.memory.write_ptr(ptr_field, data)?;
+        }
+        self.memory.write_usize(dest.offset(cap_offset, layout)?, len)?;
+        self.memory.write_usize(dest.offset(len_offset, layout)?, len)
+    }
+
+    /// The offsets of the pointer, the capacity and the length of the `Vec<u8>` that a value of
+    /// type `ty` is, or wraps like a `String` does.
+    pub(super) fn byte_vec_offsets(&self, ty: Ty<'tcx>) -> EvalResult<'tcx, (u64, u64, u64)> {
+        let substs_u8 = self.tcx.mk_substs([Kind::from(self.tcx.types.u8)].iter());
+        let field = |ecx: &Self, ty, name| {
+            field_ty_and_offset(ecx, ty, substs_u8, name)
+                .unwrap_or_else(|| bug!("{:?} has no field `{}`", ty, name))
+        };
+
+        // Unwrap single-field structs until the `Vec<u8>`, which has a `buf`.
+        let (mut vec_ty, mut vec_offset) = (ty, 0);
+        while field_ty_and_offset(self, vec_ty, substs_u8, "buf").is_none() {
+            let layout = self.layout_of(vec_ty)?;
+            vec_offset += layout.fields.offset(0).bytes();
+            vec_ty = layout.field(self, 0)?.ty;
+        }
+        let (_, len_offset) = field(self, vec_ty, "len");
+        let (buf_ty, buf_offset) = field(self, vec_ty, "buf");
+        let (_, ptr_offset) = field(self, buf_ty, "ptr");
+        let (_, cap_offset) = field(self, buf_ty, "cap");
+        Ok((
+            vec_offset + buf_offset + ptr_offset,
+            vec_offset + buf_offset + cap_offset,
+            vec_offset + len_offset,
+        ))
+    }
+}
@@ -0,0 +1,182 @@
+//! A model of the file system. The program finds the files that `ExecutionConfig::file` and
+//! `ExecutionConfig::symbolic_file` give it, and no others. Symbolic files hold up to a given
+//! number of symbolic bytes, labelled `file[PATH]`, and paths fork on their length when the
+//! program first reads them.
+//!
+//! `std::fs` reaches files through a handful of C functions, which are modelled here for
+//! reading: `open`, `read`, `fstat`, `fcntl` and `close`. Failures set `errno` to the codes
+//! Linux uses. Writing to files is not supported.
+
+use std::cmp;
+
+use rustc::ty::{self, Ty};
+use rustc::ty::subst::Substs;
+
+use error::{EvalError, EvalResult};
+use eval_context::{EvalContext, OpenFile, SymbolicString, VirtualFile};
+use executor::FileContents;
+use format_executor::field_ty_and_offset;
+use memory::{MemoryPointer, SByte};
+use value::{PrimVal, Value};
+
+const O_ACCMODE: i32 = 0o3;
+const O_RDONLY: i32 = 0;
+const O_CREAT: i32 = 0o100;
+const O_TRUNC: i32 = 0o1000;
+const F_GETFD: i32 = 1;
+const F_SETFD: i32 = 2;
+const FD_CLOEXEC: i32 = 1;
+const S_IFREG: u32 = 0o100000;
+const ENOENT: i32 = 2;
+const EBADF: i32 = 9;
+
+/// The lowest file descriptor after those of stdin, stdout and stderr.
+const FIRST_FD: i32 = 3;
+
+impl<'a, 'tcx> EvalContext<'a, 'tcx> {
+    /// Sets up the virtual file system with `files`, by path.
+    pub(crate) fn init_files(&mut self, files: &[(String, FileContents)]) {
+        for &(ref path, ref contents) in files {
+            let contents = match *contents {
+                FileContents::Concrete(ref bytes) => SymbolicString {
+                    bytes: bytes.iter().cloned().map(SByte::Concrete).collect(),
+                    len: PrimVal::Bytes(bytes.len() as u128),
+                },
+                FileContents::Symbolic(max_len) => {
+                    let label = format!("file[{}]", path);
+                    let (len, bytes) =
+                        self.memory.constraints.fresh_symbolic_buffer(label, max_len as u32);
+                    SymbolicString { bytes, len }
+                }
+            };
+            self.files.push(VirtualFile { path: path.as_bytes().to_vec(), contents });
+        }
+    }
+
+    /// Evaluates a call to the C function `name`, one of those that `std::fs` reads files
+    /// with, and returns its result. `arg_tys` are the types of `args`.
+    pub(super) fn call_fs_fn(
+        &mut self,
+        name: &str,
+        args: &[Value],
+        arg_tys: &[Ty<'tcx>],
+    ) -> EvalResult<'tcx, PrimVal> {
+        let i32 = self.tcx.types.i32;
+        let usize = self.tcx.types.usize;
+        match name {
+            "open" | "open64" => {
+                let path_ptr = args[0].read_ptr(&self.memory)?.to_ptr()?;
+                let path = self.memory.read_c_str(path_ptr)?;
+                let flags = self.value_to_primval(args[1], i32)?.to_i32()?;
+                if flags & O_ACCMODE != O_RDONLY || flags & (O_CREAT | O_TRUNC) != 0 {
+                    return Err(EvalError::Unimplemented(format!(
+                        "opening {} for writing", String::from_utf8_lossy(&path))));
+                }
+                let file = match self.files.iter().position(|file| file.path == path) {
+                    Some(file) => file,
+                    None => return self.c_error(ENOENT),
+                };
+                let fd = (FIRST_FD..).find(|fd| !self.open_files.contains_key(fd)).unwrap();
+                self.open_files.insert(fd, OpenFile { file, pos: 0 });
+                Ok(PrimVal::from_i128(fd as i128))
+            }
+            "read" => {
+                let fd = self.value_to_primval(args[0], i32)?.to_i32()?;
+                let buf = args[1].read_ptr(&self.memory)?;
+                let count = self.value_to_primval(args[2], usize)?.to_u64()?;
+                let (file, pos) = match self.open_files.get(&fd) {
+                    Some(open) => (open.file, open.pos),
+                    None => return self.c_error(EBADF),
+                };
+                let contents = self.files[file].contents.clone();
+                // May fork over the possible lengths, so it has to come first.
+                let len = self.symbolic_string_len(&contents)?;
+                let n = cmp::min(count, len.saturating_sub(pos));
+                if n > 0 {
+                    let bytes = &contents.bytes[pos as usize..(pos + n) as usize];
+                    self.memory.write_sbytes(buf.to_ptr()?, bytes)?;
+                }
+                self.open_files.get_mut(&fd).unwrap().pos = pos + n;
+                Ok(PrimVal::Bytes(n as u128))
+            }
+            "fstat" | "fstat64" => {
+                let fd = self.value_to_primval(args[0], i32)?.to_i32()?;
+                let buf = args[1].read_ptr(&self.memory)?.to_ptr()?;
+                let file = match self.open_files.get(&fd) {
+                    Some(open) => open.file,
+                    None => return self.c_error(EBADF),
+                };
+                let contents = self.files[file].contents.clone();
+                // May fork over the possible lengths, so it has to come first.
+                let len = self.symbolic_string_len(&contents)?;
+
+                // Only the type and size of the file are filled in, everything else is zero.
+                let stat_ty = match arg_tys[1].sty {
+                    ty::TyRawPtr(ref tm) => tm.ty,
+                    _ => bug!("{} takes a {:?}", name, arg_tys[1]),
+                };
+                let size = self.type_size(stat_ty)?.expect("struct stat is sized");
+                self.memory.write_repeat(buf, 0, size)?;
+                self.write_stat_field(buf, stat_ty, "st_mode", (S_IFREG | 0o444) as u128)?;
+                self.write_stat_field(buf, stat_ty, "st_size", len as u128)?;
+                Ok(PrimVal::Bytes(0))
+            }
+            "fcntl" => {
+                let fd = self.value_to_primval(args[0], i32)?.to_i32()?;
+                let cmd = self.value_to_primval(args[1], i32)?.to_i32()?;
+                if !self.open_files.contains_key(&fd) {
+                    return self.c_error(EBADF);
+                }
+                // Files are always opened with `O_CLOEXEC`.
+                match cmd {
+                    F_GETFD => Ok(PrimVal::from_i128(FD_CLOEXEC as i128)),
+                    F_SETFD => Ok(PrimVal::Bytes(0)),
+                    _ => Err(EvalError::Unimplemented(format!("fcntl() with command {}", cmd))),
+                }
+            }
+            "close" => {
+                let fd = self.value_to_primval(args[0], i32)?.to_i32()?;
+                match self.open_files.remove(&fd) {
+                    Some(_) => Ok(PrimVal::Bytes(0)),
+                    None => self.c_error(EBADF),
+                }
+            }
+            "__errno_location" => Ok(PrimVal::Ptr(self.errno_ptr()?)),
+            _ => bug!("{} is not a file system function", name),
+        }
+    }
+
+    /// Sets `errno` to `code` and returns -1, like a failing C function.
+    fn c_error(&mut self, code: i32) -> EvalResult<'tcx, PrimVal> {
+        let errno = self.errno_ptr()?;
+        self.memory.write_uint(errno, code as u128, 4)?;
+        Ok(PrimVal::from_i128(-1))
+    }
+
+    /// Where `errno` is stored. It lives as long as the program, like the thread-local variable
+    /// of the C library.
+    fn errno_ptr(&mut self) -> EvalResult<'tcx, MemoryPointer> {
+        if let Some(ptr) = self.errno {
+            return Ok(ptr);
+        }
+        let ptr = self.memory.allocate(4, 4)?;
+        self.memory.write_uint(ptr, 0, 4)?;
+        self.memory.mark_static_initalized(ptr.alloc_id, true)?;
+        self.errno = Some(ptr);
+        Ok(ptr)
+    }
+
+    /// Writes `value` to the field `name` of the `struct stat` of type `stat_ty` at `buf`.
+    fn write_stat_field(
+        &mut self,
+        buf: MemoryPointer,
+        stat_ty: Ty<'tcx>,
+        name: &str,
+        value: u128,
+    ) -> EvalResult<'tcx> {
+        let (field_ty, offset) = field_ty_and_offset(self, stat_ty, Substs::empty(), name)
+            .unwrap_or_else(|| bug!("{:?} has no field `{}`", stat_ty, name));
+        let size = self.type_size(field_ty)?.expect("fields of struct stat are sized");
+        self.memory.write_uint(buf.offset(offset, self.memory.layout)?, value, size)
+    }
+}
@@ -141,7 +141,7 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                 self.write_primval(Place::from_ptr(ptr), val, ty)?;
             },
 
-            "breakpoint" => unimplemented!(), // halt miri
+            "breakpoint" => return Err(EvalError::Unimplemented("breakpoint intrinsic".to_string())), // halt miri
 
             "copy" |
             "copy_nonoverlapping" => {
@@ -337,12 +337,12 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                         mir::BinOp::Mul,
                         PrimVal::Bytes(size as u128),
                         offset_primval,
-                        PrimValKind::U64);
+                        PrimValKind::U64)?;
                     let new_offset = self.memory.constraints.add_binop_constraint(
                         mir::BinOp::Add,
                         ptr.to_ptr()?.offset.as_primval(),
                         byte_offset,
-                        PrimValKind::U64);
+                        PrimValKind::U64)?;
                     let new_ptr = MemoryPointer::with_primval_offset(ptr.to_ptr()?.alloc_id, new_offset);
                     self.write_primval(dest, PrimVal::Ptr(new_ptr), dest_ty)?;
                 }
@@ -435,7 +435,7 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                 let dest_ty = substs.type_at(1);
                 let size = self.type_size(dest_ty)?.expect("transmute() type must be sized");
                 let ptr = self.force_allocation(dest)?.to_ptr()?;
-                self.memory.mark_packed(ptr, size);
+                self.memory.mark_packed(ptr, size)?;
                 self.write_value_to_ptr(arg_vals[0], PrimVal::Ptr(ptr), src_ty)?;
             }
 
@@ -635,27 +635,28 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                         Ok(PrimVal::Abstract(sbytes))
                     }
                     "ctlz" => {
-                        Ok(self.memory.constraints.add_intrinsic_constraint(
+                        self.memory.constraints.add_intrinsic_constraint(
                             ::constraints::NumericIntrinsic::Ctlz,
                             val,
-                            kind))
+                            kind)
                     }
                     "ctpop" => {
-                        Ok(self.memory.constraints.add_intrinsic_constraint(
+                        self.memory.constraints.add_intrinsic_constraint(
                             ::constraints::NumericIntrinsic::Ctpop,
                             val,
-                            kind))
+                            kind)
                     }
                     "cttz" => {
-                        Ok(self.memory.constraints.add_intrinsic_constraint(
+                        self.memory.constraints.add_intrinsic_constraint(
                             ::constraints::NumericIntrinsic::Cttz,
                             val,
-                            kind))
+                            kind)
                     }
-                    _ => unimplemented!(),
+                    _ => bug!("not a numeric intrinsic: {}", name),
                 }
             }
-            _ => unimplemented!(),
+            _ => Err(EvalError::Unimplemented(
+                format!("numeric intrinsic {} on {:?}", name, val))),
         }
 
     }
@@ -18,7 +18,11 @@ use rustc_data_structures::indexed_vec::Idx;
 use regex::Regex;
 
 mod drop;
+mod env;
+mod fs;
 mod intrinsic;
+mod panic;
+mod stdin;
 
 impl<'a, 'tcx> EvalContext<'a, 'tcx> {
     pub(super) fn goto_block(&mut self, target: mir::BasicBlock) {
@@ -164,23 +168,7 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                         self.goto_block(target);
                         Ok(None)
                     } else {
-                        use rustc::mir::interpret::EvalErrorKind::*;
-                        match *msg {
-                            BoundsCheck { ref len, ref index } => {
-                                let span = terminator.source_info.span;
-                                let len = self.eval_operand_to_primval(len)
-                                    .expect("can't eval len")
-                                    .to_u64()?;
-                                let index = self.eval_operand_to_primval(index)
-                                    .expect("can't eval index")
-                                    .to_u64()?;
-                                Err(EvalError::ArrayIndexOutOfBounds(span, len, index))
-                            }
-                            Overflow(op) => Err(EvalError::Overflow(op)),
-                            OverflowNeg => Err(EvalError::OverflowNeg),
-                            RemainderByZero => Err(EvalError::RemainderByZero),
-                            _ => unimplemented!(),
-                        }
+                        Err(self.assert_message_to_error(msg, terminator.source_info.span)?)
                     }
                 } else {
                     let expected_val = PrimVal::from_bool(expected);
@@ -206,20 +194,7 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                     }
 
                     if self.memory.constraints.is_feasible_with(&fail_constraints[..]) {
-                        use rustc::mir::interpret::EvalErrorKind::*;
-                        let e = match *msg {
-                            BoundsCheck { ref len, ref index } => {
-                                let span = terminator.source_info.span;
-                                let len = self.eval_operand_to_primval(len)
-                                    .expect("can't eval len")
-                                    .to_u64()?;
-                                let index = self.eval_operand_to_primval(index)
-                                    .expect("can't eval index")
-                                    .to_u64()?;
-                                EvalError::ArrayIndexOutOfBounds(span, len, index)
-                            },
-                            _ => unimplemented!(),
-                        };
+                        let e = self.assert_message_to_error(msg, terminator.source_info.span)?;
 
                         finish_steps.push(
                             FinishStep {
@@ -232,14 +207,42 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                 }
             },
 
-            DropAndReplace { .. } => unimplemented!(),
-            Resume => unimplemented!(),
-            Abort => unimplemented!(),
             Unreachable => Err(EvalError::Unreachable),
-            Yield { .. } => unimplemented!(),
-            GeneratorDrop => unimplemented!(),
-            FalseEdges { .. } => unimplemented!(),
-            FalseUnwind { .. } => unimplemented!(),
+
+            DropAndReplace { .. } |
+            Resume |
+            Abort |
+            Yield { .. } |
+            GeneratorDrop |
+            FalseEdges { .. } |
+            FalseUnwind { .. } => {
+                Err(EvalError::Unimplemented(format!("{:?} terminator", terminator.kind)))
+            }
+        }
+    }
+
+    /// The error that a failed `Assert` terminator with the given message stands for.
+    fn assert_message_to_error(
+        &mut self,
+        msg: &mir::AssertMessage<'tcx>,
+        span: Span,
+    ) -> EvalResult<'tcx, EvalError<'tcx>> {
+        use rustc::mir::interpret::EvalErrorKind::*;
+        match *msg {
+            BoundsCheck { ref len, ref index } => {
+                let len = self.eval_operand_to_primval(len)
+                    .expect("can't eval len")
+                    .to_u64()?;
+                let index = self.eval_operand_to_primval(index)
+                    .expect("can't eval index")
+                    .to_u64()?;
+                Ok(EvalError::ArrayIndexOutOfBounds(span, len, index))
+            }
+            Overflow(op) => Ok(EvalError::Overflow(op)),
+            OverflowNeg => Ok(EvalError::OverflowNeg),
+            DivisionByZero => Ok(EvalError::DivisionByZero),
+            RemainderByZero => Ok(EvalError::RemainderByZero),
+            _ => Err(EvalError::Unimplemented(format!("assertion failure {:?}", msg))),
         }
     }
 
@@ -383,7 +386,10 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                         return self.call_c_abi(instance.def_id(), arg_operands, ret, ty, target);
                     },
                     Abi::Rust | Abi::RustCall => {},
-                    _ => unimplemented!(),
+                    abi => {
+                        return Err(EvalError::Unimplemented(
+                            format!("calling a function with the {} ABI", abi)));
+                    }
                 }
                 let mut args = Vec::new();
                 for arg in arg_operands {
@@ -478,7 +484,10 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                             );
                         }
                     }
-                    _ => unimplemented!(),
+                    abi => {
+                        return Err(EvalError::Unimplemented(
+                            format!("calling a function with the {} ABI", abi)));
+                    }
                 }
                 Ok(None)
             },
@@ -554,6 +563,21 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                         self.goto_block(block);
                         return Ok(true);
                     }
+                    "seer_helper::mksym_env" => {
+                        let (_lval, block) = destination.expect("seer_helper::mksym_env() does not diverge");
+                        let name = match self.eval_operand(&arg_operands[0])? {
+                            Value::ByValPair(PrimVal::Ptr(ptr), PrimVal::Bytes(len)) => {
+                                self.read_concrete_bytes(ptr, len as u64)?
+                            }
+                            other => {
+                                return Err(EvalError::Unimplemented(
+                                    format!("seer_helper::mksym_env() of {:?}", other)));
+                            }
+                        };
+                        self.declare_symbolic_env_var(&name)?;
+                        self.goto_block(block);
+                        return Ok(true);
+                    }
                     "std::io::stdin" => {
                         let (_lval, block) = destination.expect("std::io::stdin() does not diverge");
                         self.goto_block(block);
@@ -565,34 +589,13 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                             .map(|arg| self.eval_operand(arg))
                             .collect();
                         let args = args_res?;
-
-                        let num_bytes = match args[1] {
-                            Value::ByValPair(PrimVal::Ptr(ptr), PrimVal::Bytes(len)) => {
-                                self.memory.write_fresh_abstract_bytes(ptr, len as u64)?;
-                                len
-                            }
-                            _ => {
-                                unimplemented!()
-                            }
-                        };
-
-                        let _ = sig.output();
-
-                        // Write `Ok(num_bytes)` to the return value.
-                        let dest_ptr = self.force_allocation(lval)?.to_ptr()?;
-
-                        let usize_bytes = self.memory.pointer_size();
-
-                        // FIXME make this more robust
-                        self.memory.write_uint(dest_ptr, 0, usize_bytes)?; // discriminant = Ok
-                        self.memory.write_uint(
-                            dest_ptr.offset(usize_bytes, self.memory.layout)?,
-                            num_bytes, usize_bytes)?; // payload
-
+                        self.call_stdin_read(lval, sig.output(), args[1])?;
                         self.goto_block(block);
                         return Ok(true);
                     }
-                    "<std::io::Stdin as std::io::Read>::read_exact" => {
+                    // With short reads, the default `read_exact` of the lock calls `read` until
+                    // the buffer is full.
+                    "<std::io::Stdin as std::io::Read>::read_exact" if !self.stdin.short_reads => {
                         let (lval, block) = destination.expect("Stdin::read() does not diverge");
                         let args_res: EvalResult<Vec<Value>> = arg_operands.iter()
                             .map(|arg| self.eval_operand(arg))
@@ -601,10 +604,11 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
 
                         match args[1] {
                             Value::ByValPair(PrimVal::Ptr(ptr), PrimVal::Bytes(len)) => {
-                                self.memory.write_fresh_abstract_bytes(ptr, len as u64)?;
+                                self.read_stdin(ptr, len as u64)?;
                             }
-                            _ => {
-                                unimplemented!()
+                            other => {
+                                return Err(EvalError::Unimplemented(
+                                    format!("Stdin::read_exact() into buffer {:?}", other)));
                             }
                         }
 
@@ -619,14 +623,37 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                         self.goto_block(block);
                         return Ok(true);
                     }
+                    "std::env::args" |
+                    "<std::env::Args as std::iter::Iterator>::next" |
+                    "<std::env::Args as std::iter::Iterator>::size_hint" |
+                    "<std::env::Args as std::iter::ExactSizeIterator>::len" => {
+                        let path = self.tcx.item_path_str(def_id);
+                        let args_res: EvalResult<Vec<Value>> = arg_operands.iter()
+                            .map(|arg| self.eval_operand(arg))
+                            .collect();
+                        let args = args_res?;
+                        self.call_args_fn(&path, destination, &args, sig.output())?;
+                        return Ok(true);
+                    }
+                    "std::env::_var_os" => {
+                        let args_res: EvalResult<Vec<Value>> = arg_operands.iter()
+                            .map(|arg| self.eval_operand(arg))
+                            .collect();
+                        let args = args_res?;
+                        self.call_var_os(destination, &args, sig.output())?;
+                        return Ok(true);
+                    }
 
                     "std::io::Stdin::lock" => {
-                        return Err(
-                            EvalError::Unimplemented(
-                                "no abstract implementation for stdin.lock()".into()));
+                        let (_lval, block) = destination.expect("Stdin::lock() does not diverge");
+                        self.goto_block(block);
+                        return Ok(true);
                     }
                     _ => (),
                 }
+                if self.call_stdin_lock_fn(instance, destination, arg_operands, sig)? {
+                    return Ok(true);
+                }
             }
             _ => (),
         }
@@ -658,6 +685,25 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
         Ok(false)
     }
 
+    /// The size of an allocation as a number of bytes. If the size depends on the input,
+    /// asks `step` to fork over its possible values, unless the path constraints already
+    /// leave only one. Must be called before the current step has any side effects.
+    fn concrete_alloc_size(&mut self, size: PrimVal) -> EvalResult<'tcx, u64> {
+        if size.is_concrete() {
+            return size.to_u64();
+        }
+        let kind = PrimValKind::U64;
+        match self.memory.constraints.unique_value(size, kind, self.symbolic_alloc_limit as u128) {
+            Ok(size) => Ok(size as u64),
+            Err(candidates) => Err(EvalError::NeedsConcreteValue {
+                value: size,
+                kind,
+                candidates,
+                otherwise: Box::new(EvalError::SymbolicAllocLimitReached(self.symbolic_alloc_limit)),
+            }),
+        }
+    }
+
     /// Returns Ok() when the function was handled, fail otherwise
     fn call_missing_fn(
         &mut self,
@@ -690,9 +736,9 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                 Ok(())
             },
             "std::thread::Builder::new" => Err(EvalError::Unimplemented("miri does not support threading".to_owned())),
-            "std::env::args" => Err(EvalError::Unimplemented("miri does not support program arguments".to_owned())),
             "std::panicking::rust_panic_with_hook" |
-            "std::rt::begin_panic_fmt" => Err(EvalError::Panic),
+            "std::rt::begin_panic_fmt" |
+            "core::panicking::panic" => Err(self.panic_error(&path, arg_operands, &args)),
             "std::panicking::panicking" |
             "std::rt::panicking" => {
                 let (lval, block) = destination.expect("std::rt::panicking does not diverge");
@@ -703,23 +749,24 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                 Ok(())
             }
             "alloc::allocator::Layout::from_size_align" => {
-                let (lval, block) = destination.expect("from_size_align() does not diverge");
-                let dest_ptr = self.force_allocation(lval)?.to_ptr()?;
-
                 let usize = self.tcx.types.usize;
-                let size = self.value_to_primval(args[0], usize)?.to_u128()?;
+                let size = self.value_to_primval(args[0], usize)?;
+                let size = self.concrete_alloc_size(size)? as u128;
                 let align = self.value_to_primval(args[1], usize)?.to_u128()?;
 
-                if !align.is_power_of_two() {
-                    unimplemented!();
-                }
-
-                if size as usize > ::std::usize::MAX - (align as usize - 1) {
-                    unimplemented!();
-                }
+                let (lval, block) = destination.expect("from_size_align() does not diverge");
+                let dest_ptr = self.force_allocation(lval)?.to_ptr()?;
 
                 let usize_bytes = self.memory.pointer_size();
 
+                if !align.is_power_of_two() ||
+                    size as usize > ::std::usize::MAX - (align as usize - 1)
+                {
+                    self.memory.write_uint(dest_ptr, 0, usize_bytes)?; // discriminant = None
+                    self.goto_block(block);
+                    return Ok(());
+                }
+
                 // FIXME make this more robust
                 self.memory.write_uint(dest_ptr, 1, usize_bytes)?; // discriminant = Some
 
@@ -735,7 +782,8 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
 
             "alloc::alloc::::__rust_alloc" => {
                 let usize = self.tcx.types.usize;
-                let size = self.value_to_primval(args[0], usize)?.to_u64()?;
+                let size = self.value_to_primval(args[0], usize)?;
+                let size = self.concrete_alloc_size(size)?;
                 let align = self.value_to_primval(args[1], usize)?.to_u64()?;
                 let ptr = self.memory.allocate(size, align)?;
 
@@ -749,7 +797,8 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
 
             "alloc::alloc::::__rust_alloc_zeroed" => {
                 let usize = self.tcx.types.usize;
-                let size = self.value_to_primval(args[0], usize)?.to_u64()?;
+                let size = self.value_to_primval(args[0], usize)?;
+                let size = self.concrete_alloc_size(size)?;
                 let align = self.value_to_primval(args[1], usize)?.to_u64()?;
                 let ptr = self.memory.allocate(size, align)?;
                 self.memory.write_repeat(ptr, 0, size)?;
@@ -803,17 +852,17 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
                     len_rounded_up.wrapping_sub(len)
                 };
 
-                let padded_size = match self_size.checked_add(padding_needed) {
-                    None => unimplemented!(), // return None
-                    Some(padded_size) => padded_size,
-                };
-                let alloc_size = match padded_size.checked_mul(n) {
-                    None => unimplemented!(), // return None
-                    Some(alloc_size) => alloc_size,
+                let sizes = self_size.checked_add(padding_needed)
+                    .and_then(|padded_size| padded_size.checked_mul(n).map(|alloc_size| (padded_size, alloc_size)));
+                let (padded_size, alloc_size) = match sizes {
+                    Some(sizes) => sizes,
+                    None => {
+                        self.memory.write_uint(dest_ptr, 0, usize_bytes)?; // discriminant = None
+                        self.goto_block(block);
+                        return Ok(());
+                    }
                 };
 
-                let usize_bytes = self.memory.pointer_size();
-
                 self.memory.write_uint(dest_ptr, 1, usize_bytes)?; // discriminant = Some
 
                 // payload
@@ -830,18 +879,21 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
             }
 
             "alloc::alloc::::__rust_realloc" => {
-                let (lval, block) = destination.expect("realloc() does not diverge");
-                let dest_ptr = self.force_allocation(lval)?.to_ptr()?;
-
                 let ptr = match args[0] {
                     Value::ByVal(PrimVal::Ptr(p)) => p,
-                    _ => unimplemented!(),
+                    other => {
+                        return Err(EvalError::Unimplemented(
+                            format!("__rust_realloc() of {:?}", other)));
+                    }
                 };
 
                 let usize = self.tcx.types.usize;
-                let _old_size = self.value_to_primval(args[1], usize)?.to_u64()?;
+                let new_size = self.value_to_primval(args[3], usize)?;
+                let new_size = self.concrete_alloc_size(new_size)?;
                 let align = self.value_to_primval(args[2], usize)?.to_u64()?;
-                let new_size = self.value_to_primval(args[3], usize)?.to_u64()?;
+
+                let (lval, block) = destination.expect("realloc() does not diverge");
+                let dest_ptr = self.force_allocation(lval)?.to_ptr()?;
 
                 let new_ptr = self.memory.reallocate(ptr, new_size, align)?;
                 self.memory.write_ptr(dest_ptr, new_ptr)?;
@@ -854,7 +906,10 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
 
                 let ptr = match args[0] {
                     Value::ByVal(PrimVal::Ptr(p)) => p,
-                    _ => unimplemented!(),
+                    other => {
+                        return Err(EvalError::Unimplemented(
+                            format!("__rust_dealloc() of {:?}", other)));
+                    }
                 };
 
                 self.memory.deallocate(ptr)?;
@@ -880,6 +935,7 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
             None => self.tcx.item_name(def_id).as_str(),
         };
 
+        let arg_tys: Vec<Ty<'tcx>> = args.iter().map(|arg| self.operand_ty(arg)).collect();
         let args_res: EvalResult<Vec<Value>> = args.iter()
             .map(|arg| self.eval_operand(arg))
             .collect();
@@ -1008,7 +1064,7 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
             }
 
             "memrchr" => {
-                unimplemented!()
+                return Err(EvalError::Unimplemented("memrchr".to_string()));
                     /*
                 let ptr = args[0].read_ptr(&self.memory)?;
                 let val = self.value_to_primval(args[1], usize)?.to_u64()? as u8;
@@ -1022,28 +1078,46 @@ impl<'a, 'tcx> EvalContext<'a, 'tcx> {
             }
 
             "memchr" => {
-                unimplemented!()
-                /*
-                let ptr = args[0].read_ptr(&self.memory)?;
+                // `CString::new` looks for NUL bytes in the paths `std::fs` opens.
+                let ptr = args[0].read_ptr(&self.memory)?.to_ptr()?;
                 let val = self.value_to_primval(args[1], usize)?.to_u64()? as u8;
                 let num = self.value_to_primval(args[2], usize)?.to_u64()?;
-                if let Some(idx) = self.memory.read_bytes(ptr, num)?.iter().position(|&c| c == val) {
-                    let new_ptr = ptr.offset(idx as u64);
-                    self.write_value(Value::ByVal(PrimVal::Ptr(new_ptr)), dest, dest_ty)?;
-                } else {
-                    self.write_value(Value::ByVal(PrimVal::Bytes(0)), dest, dest_ty)?;
-                }*/
+                let found = {
+                    let bytes = self.memory.read_bytes(PrimVal::Ptr(ptr), num)?;
+                    let mut found = None;
+                    for (idx, byte) in bytes.iter().enumerate() {
+                        match *byte {
+                            SByte::Concrete(b) if b == val => {
+                                found = Some(idx as u64);
+                                break;
+                            }
+                            SByte::Concrete(_) => {}
+                            SByte::Abstract(_) => {
+                                return Err(EvalError::Unimplemented(
+                                    "memchr over symbolic bytes".to_string()));
+                            }
+                        }
+                    }
+                    found
+                };
+                let result = match found {
+                    Some(idx) => PrimVal::Ptr(ptr.offset(idx, self.memory.layout)?),
+                    None => PrimVal::Bytes(0),
+                };
+                self.write_primval(dest, result, dest_ty)?;
+                self.goto_block(target);
             }
 
             "getenv" => {
-                let result = {
-                    let name_ptr = args[0].read_ptr(&self.memory)?.to_ptr()?;
-                    let name = self.memory.read_c_str(name_ptr)?;
-                    match self.env_vars.get(name) {
-                        Some(&var) => PrimVal::Ptr(var),
-                        None => PrimVal::Bytes(0),
-                    }
-                };
+                let name_ptr = args[0].read_ptr(&self.memory)?.to_ptr()?;
+                let result = self.getenv(name_ptr)?;
+                self.write_primval(dest, result, dest_ty)?;
+                self.goto_block(target);
+            }
+
+            "open" | "open64" | "read" | "fstat" | "fstat64" | "fcntl" | "close" |
+            "__errno_location" => {
+                let result = self.call_fs_fn(&link_name, &args, &arg_tys)?;
                 self.write_primval(dest, result, dest_ty)?;
                 self.goto_block(target);
             }
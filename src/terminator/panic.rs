@@ -0,0 +1,263 @@
+//! Recovers the message and location of a panic from the arguments of the library functions
+//! that start one, so that different panics can be told apart.
+
+use rustc::mir;
+use rustc::ty::{self, Ty};
+use rustc::ty::layout::LayoutOf;
+
+use error::{EvalError, PanicLocation};
+use eval_context::EvalContext;
+use memory::{MemoryPointer, SByte};
+use value::{PrimVal, Value};
+
+/// Stands in for a formatting argument that depends on the input, or that seer cannot format.
+const UNKNOWN_ARGUMENT: &str = "{..}";
+
+impl<'a, 'tcx> EvalContext<'a, 'tcx> {
+    /// The error for a call to `path`, one of the functions that start a panic. The message and
+    /// location are left out if they are not concrete.
+    pub(super) fn panic_error(
+        &self,
+        path: &str,
+        arg_operands: &[mir::Operand<'tcx>],
+        args: &[Value],
+    ) -> EvalError<'tcx> {
+        let arg_ty = |idx: usize| self.operand_ty(&arg_operands[idx]);
+        let (message, location) = match path {
+            // fn begin_panic_fmt(msg: &fmt::Arguments, file_line_col: &(&'static str, u32, u32))
+            "std::rt::begin_panic_fmt" => (
+                self.format_arguments_at(args[0], arg_ty(0)),
+                self.panic_location(args[1], arg_ty(1), 0),
+            ),
+            // fn rust_panic_with_hook(payload: &mut BoxMeUp, message: Option<&fmt::Arguments>,
+            //                         file_line_col: &(&str, u32, u32))
+            "std::panicking::rust_panic_with_hook" => {
+                let message = match args[1] {
+                    // `Option<&fmt::Arguments>` has the same representation as the reference.
+                    Value::ByVal(PrimVal::Ptr(_)) => match arg_ty(1).sty {
+                        ty::TyAdt(_, substs) => {
+                            self.format_arguments_at(args[1], substs.type_at(0))
+                        }
+                        _ => None,
+                    },
+                    // No formatted message, so the payload is what `begin_panic` was called with.
+                    _ => self.begin_panic_payload(args[0]),
+                };
+                (message, self.panic_location(args[2], arg_ty(2), 0))
+            }
+            // fn panic(expr_file_line_col: &(&'static str, &'static str, u32, u32))
+            "core::panicking::panic" => {
+                let message = self.pointee_field(args[0], arg_ty(0), 0)
+                    .and_then(|(ptr, _)| self.read_str_ref(ptr));
+                (message, self.panic_location(args[0], arg_ty(0), 1))
+            }
+            _ => bug!("{} does not start a panic", path),
+        };
+        EvalError::Panic { message, location }
+    }
+
+    /// The payload of a `panic!` with a single argument, if that is a string literal.
+    fn begin_panic_payload(&self, payload: Value) -> Option<String> {
+        let frame = self.stack().last()?;
+        match &self.tcx.item_path_str(frame.instance.def_id())[..] {
+            "std::rt::begin_panic" | "std::panicking::begin_panic" => {}
+            _ => return None,
+        }
+        if !is_str_ref(frame.instance.substs.type_at(0)) {
+            return None;
+        }
+        // The payload is a `PanicPayload { inner: Option<&str> }`. Neither wrapper changes the
+        // representation of the string reference.
+        match payload {
+            Value::ByValPair(PrimVal::Ptr(ptr), _) => self.read_str_ref(ptr),
+            _ => None,
+        }
+    }
+
+    /// Reads the file, line and column stored in consecutive fields of the tuple or struct that
+    /// `value`, a reference of type `ty`, points to, starting at field `first`.
+    fn panic_location(&self, value: Value, ty: Ty<'tcx>, first: usize) -> Option<PanicLocation> {
+        let (file_ptr, _) = self.pointee_field(value, ty, first)?;
+        let (line_ptr, _) = self.pointee_field(value, ty, first + 1)?;
+        let (column_ptr, _) = self.pointee_field(value, ty, first + 2)?;
+        Some(PanicLocation {
+            file: self.read_str_ref(file_ptr)?,
+            line: self.read_concrete_uint(line_ptr, 4)? as u32,
+            column: self.read_concrete_uint(column_ptr, 4)? as u32,
+        })
+    }
+
+    /// Formats the `fmt::Arguments` that `value`, a reference of type `ty`, points to.
+    fn format_arguments_at(&self, value: Value, ty: Ty<'tcx>) -> Option<String> {
+        let (pieces_ptr, _) = self.pointee_field(value, ty, 0)?;
+        let (fmt_ptr, _) = self.pointee_field(value, ty, 1)?;
+        let (args_ptr, args_ty) = self.pointee_field(value, ty, 2)?;
+
+        let pieces = self.read_slice_ref(pieces_ptr)?;
+        let pointer_size = self.memory.pointer_size();
+        let pieces: Vec<Option<String>> = (0..pieces.1)
+            .map(|idx| {
+                let piece = pieces.0.offset(idx * 2 * pointer_size, self.memory.layout).ok()?;
+                self.read_str_ref(piece)
+            })
+            .collect();
+
+        // With explicit format specs, `fmt` says which argument goes where and how. Only the
+        // default formatting of the arguments in order is reproduced here.
+        let default_specs = match self.memory.read_ptr(fmt_ptr).ok()? {
+            PrimVal::Bytes(0) => true,
+            _ => false,
+        };
+
+        let (args, num_args) = self.read_slice_ref(args_ptr)?;
+        let arg_ty = match args_ty.sty {
+            ty::TyRef(_, slice, _) => match slice.sty {
+                ty::TySlice(elem) => elem,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let arg_size = self.layout_of(arg_ty).ok()?.size.bytes();
+
+        let mut message = String::new();
+        for (idx, piece) in pieces.into_iter().enumerate() {
+            message.push_str(piece.as_ref().map(|s| &s[..]).unwrap_or(UNKNOWN_ARGUMENT));
+            if idx as u64 >= num_args {
+                continue;
+            }
+            let formatted = if default_specs {
+                args.offset(idx as u64 * arg_size, self.memory.layout).ok()
+                    .and_then(|arg| self.format_argument(arg, arg_ty))
+            } else {
+                None
+            };
+            message.push_str(formatted.as_ref().map(|s| &s[..]).unwrap_or(UNKNOWN_ARGUMENT));
+        }
+        Some(message)
+    }
+
+    /// Formats a `fmt::ArgumentV1`, which holds a reference to the value and the `fmt` method
+    /// of the `Display` or `Debug` impl to use.
+    fn format_argument(&self, arg: MemoryPointer, arg_ty: Ty<'tcx>) -> Option<String> {
+        let layout = self.layout_of(arg_ty).ok()?;
+        let value_field = arg.offset(layout.fields.offset(0).bytes(), self.memory.layout).ok()?;
+        let formatter_field = arg.offset(layout.fields.offset(1).bytes(), self.memory.layout).ok()?;
+
+        let formatter = match self.memory.read_ptr(formatter_field).ok()? {
+            PrimVal::Ptr(ptr) => self.memory.get_fn(ptr).ok()?,
+            _ => return None,
+        };
+        let impl_def_id = self.tcx.impl_of_method(formatter.def_id())?;
+        let trait_def_id = self.tcx.trait_id_of_impl(impl_def_id)?;
+        let debug = match &self.tcx.item_path_str(trait_def_id)[..] {
+            "core::fmt::Display" | "std::fmt::Display" => false,
+            "core::fmt::Debug" | "std::fmt::Debug" => true,
+            _ => return None,
+        };
+        let self_ty = self.monomorphize(self.tcx.type_of(impl_def_id), formatter.substs);
+
+        match self.memory.read_ptr(value_field).ok()? {
+            PrimVal::Ptr(value) => self.format_value(value, self_ty, debug),
+            _ => None,
+        }
+    }
+
+    /// Formats the value of type `ty` at `ptr` like `Display` or `Debug` would, for the types
+    /// whose output is easy to reproduce.
+    fn format_value(&self, ptr: MemoryPointer, ty: Ty<'tcx>, debug: bool) -> Option<String> {
+        match ty.sty {
+            _ if is_str_ref(ty) => {
+                let s = self.read_str_ref(ptr)?;
+                Some(if debug { format!("{:?}", s) } else { s })
+            }
+            ty::TyRef(_, pointee, _) => match self.memory.read_ptr(ptr).ok()? {
+                PrimVal::Ptr(target) => self.format_value(target, pointee, debug),
+                _ => None,
+            },
+            ty::TyBool => {
+                Some((self.read_concrete_uint(ptr, 1)? != 0).to_string())
+            }
+            ty::TyChar => {
+                let c = ::std::char::from_u32(self.read_concrete_uint(ptr, 4)? as u32)?;
+                Some(if debug { format!("{:?}", c) } else { c.to_string() })
+            }
+            ty::TyUint(_) => {
+                let size = self.type_size(ty).ok()??;
+                Some(self.read_concrete_uint(ptr, size)?.to_string())
+            }
+            ty::TyInt(_) => {
+                let size = self.type_size(ty).ok()??;
+                if !self.memory.points_to_concrete(ptr, size).ok()? {
+                    return None;
+                }
+                Some(self.memory.read_int(ptr, size).ok()?.to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// A pointer to field `idx` of the value that `value`, a reference of type `ty`, points to,
+    /// and the type of that field.
+    fn pointee_field(
+        &self,
+        value: Value,
+        ty: Ty<'tcx>,
+        idx: usize,
+    ) -> Option<(MemoryPointer, Ty<'tcx>)> {
+        let ptr = match value {
+            Value::ByVal(PrimVal::Ptr(ptr)) => ptr,
+            _ => return None,
+        };
+        let pointee = match ty.sty {
+            ty::TyRef(_, pointee, _) => pointee,
+            _ => return None,
+        };
+        let layout = self.layout_of(pointee).ok()?;
+        let field = layout.field(self, idx).ok()?;
+        let field_ptr = ptr.offset(layout.fields.offset(idx).bytes(), self.memory.layout).ok()?;
+        Some((field_ptr, field.ty))
+    }
+
+    /// Reads the data pointer and length of the slice reference stored at `ptr`.
+    fn read_slice_ref(&self, ptr: MemoryPointer) -> Option<(MemoryPointer, u64)> {
+        let len_ptr = ptr.offset(self.memory.pointer_size(), self.memory.layout).ok()?;
+        let len = self.read_concrete_uint(len_ptr, self.memory.pointer_size())? as u64;
+        match self.memory.read_ptr(ptr).ok()? {
+            PrimVal::Ptr(data) => Some((data, len)),
+            _ => None,
+        }
+    }
+
+    /// Reads the string that the `&str` stored at `ptr` refers to, if all of it is concrete.
+    fn read_str_ref(&self, ptr: MemoryPointer) -> Option<String> {
+        let (data, len) = self.read_slice_ref(ptr)?;
+        if len == 0 {
+            return Some(String::new());
+        }
+        let bytes = self.memory.read_bytes(PrimVal::Ptr(data), len).ok()?;
+        let bytes: Vec<u8> = bytes.iter()
+            .map(|b| match *b {
+                SByte::Concrete(b) => Some(b),
+                SByte::Abstract(_) => None,
+            })
+            .collect::<Option<_>>()?;
+        String::from_utf8(bytes).ok()
+    }
+
+    fn read_concrete_uint(&self, ptr: MemoryPointer, size: u64) -> Option<u128> {
+        if !self.memory.points_to_concrete(ptr, size).ok()? {
+            return None;
+        }
+        self.memory.read_uint(ptr, size).ok()
+    }
+}
+
+fn is_str_ref(ty: Ty) -> bool {
+    match ty.sty {
+        ty::TyRef(_, pointee, _) => match pointee.sty {
+            ty::TyStr => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
@@ -0,0 +1,351 @@
+//! A model of stdin, for the reads that stop at a delimiter or at the end of the input, like
+//! `BufRead::read_line`, `BufRead::lines` and `Read::read_to_end`.
+//!
+//! Every byte of stdin is a fresh symbolic byte, part of the input labelled `stdin`.
+//! `Read::read` always fills the buffer it is given, but the other reads find the end of stdin
+//! after at most `ExecutionConfig::stdin_len` more bytes than the program had read when it first
+//! looked for it. Paths fork over where the end is, and over which bytes are delimiters.
+//!
+//! With `ExecutionConfig::short_reads`, `Read::read` also finds the end of stdin, and paths fork
+//! over every number of bytes it can read, from one up to the size of the buffer. A read may also
+//! fail with `ErrorKind::Interrupted` or `ErrorKind::Other` instead, once on every path.
+//!
+//! `Stdin::lock` returns a lock that holds nothing. Its `BufReader` is never used: `read_until`
+//! and `read_to_end`, which the default methods of `BufRead` and `Read` call, are evaluated here
+//! when they read from a `StdinLock`.
+
+use std::cmp;
+
+use rustc::mir;
+use rustc::ty::{self, Ty};
+use rustc::ty::layout::LayoutOf;
+
+use error::{EvalError, EvalResult};
+use eval_context::EvalContext;
+use memory::{MemoryPointer, SByte};
+use place::Place;
+use value::{PrimVal, PrimValKind, Value};
+
+/// The `io::ErrorKind`s that reads can fail with when short reads are enabled.
+const READ_ERRORS: [&str; 2] = ["Interrupted", "Other"];
+
+impl<'a, 'tcx> EvalContext<'a, 'tcx> {
+    /// Evaluates `instance` if it reads from a `StdinLock`. Returns whether it did.
+    pub(super) fn call_stdin_lock_fn(
+        &mut self,
+        instance: ty::Instance<'tcx>,
+        destination: Option<(Place<'tcx>, mir::BasicBlock)>,
+        arg_operands: &[mir::Operand<'tcx>],
+        sig: ty::FnSig<'tcx>,
+    ) -> EvalResult<'tcx, bool> {
+        let def_id = instance.def_id();
+        let path = self.tcx.item_path_str(def_id);
+        let reads_stdin_lock = match &path[..] {
+            "std::io::read_until" | "std::io::read_to_end" => {
+                self.is_stdin_lock(instance.substs.type_at(0))
+            }
+            _ => match self.tcx.impl_of_method(def_id) {
+                Some(impl_def_id) => self.is_stdin_lock(self.tcx.type_of(impl_def_id)),
+                None => false,
+            },
+        };
+        if !reads_stdin_lock {
+            return Ok(false);
+        }
+
+        let (dest, block) = destination.expect("reading stdin does not diverge");
+        let args_res: EvalResult<Vec<Value>> = arg_operands.iter()
+            .map(|arg| self.eval_operand(arg))
+            .collect();
+        let args = args_res?;
+        match &path[..] {
+            "std::io::read_until" | "std::io::read_to_end" => {
+                let delim = if path == "std::io::read_until" {
+                    let u8 = self.tcx.types.u8;
+                    Some(self.value_to_primval(args[1], u8)?.to_u64()? as u8)
+                } else {
+                    None
+                };
+                // May fork, so it has to come first.
+                let bytes = self.read_stdin_until(delim)?;
+                let vec_arg = arg_operands.len() - 1;
+                let vec_ty = match sig.inputs()[vec_arg].sty {
+                    ty::TyRef(_, ty, _) => ty,
+                    _ => bug!("{} takes a {:?}", path, sig.inputs()[vec_arg]),
+                };
+                let vec = args[vec_arg].read_ptr(&self.memory)?.to_ptr()?;
+                self.append_to_byte_vec(vec, vec_ty, &bytes)?;
+                self.write_io_ok(dest, sig.output(), bytes.len() as u64)?;
+            }
+            _ if &*self.tcx.item_name(def_id).as_str() == "read" => {
+                self.call_stdin_read(dest, sig.output(), args[1])?;
+            }
+            _ => return Err(EvalError::Unimplemented(format!("{} on a locked stdin", path))),
+        }
+        self.goto_block(block);
+        Ok(true)
+    }
+
+    /// Evaluates `Read::read` on `Stdin` or a `StdinLock` into the slice `buf`, writing the
+    /// `io::Result<usize>` of type `dest_ty` to `dest`.
+    pub(super) fn call_stdin_read(
+        &mut self,
+        dest: Place<'tcx>,
+        dest_ty: Ty<'tcx>,
+        buf: Value,
+    ) -> EvalResult<'tcx> {
+        let (ptr, len) = match buf {
+            Value::ByValPair(PrimVal::Ptr(ptr), PrimVal::Bytes(len)) => (ptr, len as u64),
+            other => {
+                return Err(EvalError::Unimplemented(
+                    format!("reading stdin into buffer {:?}", other)));
+            }
+        };
+        if !self.stdin.short_reads {
+            self.read_stdin(ptr, len)?;
+            return self.write_io_ok(dest, dest_ty, len);
+        }
+        // May fork, so it has to come first.
+        match self.short_read_outcome(len)? {
+            Ok(num_bytes) => {
+                self.read_stdin(ptr, num_bytes)?;
+                self.write_io_ok(dest, dest_ty, num_bytes)
+            }
+            Err(kind) => self.write_io_error(dest, dest_ty, kind),
+        }
+    }
+
+    /// Reads the next `len` bytes of stdin into `ptr`, without looking for its end.
+    pub(super) fn read_stdin(&mut self, ptr: MemoryPointer, len: u64) -> EvalResult<'tcx> {
+        let bytes = self.consume_stdin(len);
+        self.memory.write_sbytes(ptr, &bytes)
+    }
+
+    /// How a read of at most `len` bytes ends when short reads are enabled: with the number of
+    /// bytes it reads, none at the end of stdin, or with the `io::ErrorKind` it fails with. Asks
+    /// `step` to fork over all of them.
+    fn short_read_outcome(&mut self, len: u64) -> EvalResult<'tcx, Result<u64, &'static str>> {
+        if len == 0 {
+            return Ok(Ok(0));
+        }
+        let remaining = self.stdin_remaining()?;
+        if remaining == 0 {
+            return Ok(Ok(0));
+        }
+        let max = cmp::min(len, remaining);
+        // Outcomes up to `max` are numbers of bytes, the ones after it are errors.
+        let errors: &[&'static str] = if self.stdin.failed_read { &[] } else { &READ_ERRORS };
+        let last = max + errors.len() as u64;
+        let outcome = match self.stdin.read_outcome {
+            Some(outcome) => outcome,
+            None => {
+                // Kept across forks, like the length of stdin.
+                let outcome = self.memory.constraints.fresh_bounded_u64(1, last);
+                self.stdin.read_outcome = Some(outcome);
+                outcome
+            }
+        };
+        let kind = PrimValKind::U64;
+        let outcome = match self.memory.constraints.unique_value(outcome, kind, last as u128) {
+            Ok(outcome) => outcome as u64,
+            Err(candidates) => return Err(EvalError::NeedsConcreteValue {
+                value: outcome,
+                kind,
+                candidates,
+                // Cannot happen, the outcome is bounded when it is created.
+                otherwise: Box::new(EvalError::Unimplemented(
+                    "read outcome out of bounds".to_string())),
+            }),
+        };
+        self.stdin.read_outcome = None;
+        if outcome <= max {
+            Ok(Ok(outcome))
+        } else {
+            self.stdin.failed_read = true;
+            Ok(Err(errors[(outcome - max - 1) as usize]))
+        }
+    }
+
+    /// Reads stdin up to and including the first `delim`, or to its end if there is none.
+    /// Asks `step` to fork over where the end of stdin is and over which bytes are `delim`.
+    fn read_stdin_until(&mut self, delim: Option<u8>) -> EvalResult<'tcx, Vec<SByte>> {
+        let remaining = self.stdin_remaining()?;
+        let mut len = 0;
+        while len < remaining {
+            let byte = self.peek_stdin(len);
+            len += 1;
+            if let Some(delim) = delim {
+                if self.byte_equals(byte, delim)? {
+                    break;
+                }
+            }
+        }
+        Ok(self.consume_stdin(len))
+    }
+
+    /// The number of bytes left on stdin. If that depends on the input, asks `step` to fork
+    /// over the numbers it can be.
+    fn stdin_remaining(&mut self) -> EvalResult<'tcx, u64> {
+        let read = self.stdin.read;
+        let max = read + self.stdin.max_remaining;
+        let len = match self.stdin.len {
+            Some(len) => len,
+            None => {
+                // Kept across forks, so that every branch agrees on the same length.
+                let len = self.memory.constraints.fresh_bounded_u64(read, max);
+                self.stdin.len = Some(len);
+                len
+            }
+        };
+        if len.is_concrete() {
+            return Ok(len.to_u64()?.saturating_sub(read));
+        }
+        let kind = PrimValKind::U64;
+        match self.memory.constraints.unique_value(len, kind, max as u128) {
+            Ok(len) => {
+                self.stdin.len = Some(PrimVal::Bytes(len));
+                Ok((len as u64).saturating_sub(read))
+            }
+            Err(candidates) => Err(EvalError::NeedsConcreteValue {
+                value: len,
+                kind,
+                candidates,
+                // Cannot happen, the length is bounded when it is created.
+                otherwise: Box::new(EvalError::Unimplemented(
+                    "stdin longer than its bound".to_string())),
+            }),
+        }
+    }
+
+    /// Whether `byte` is `value`. If that depends on the input, asks `step` to fork over both
+    /// cases.
+    fn byte_equals(&mut self, byte: SByte, value: u8) -> EvalResult<'tcx, bool> {
+        if let SByte::Concrete(byte) = byte {
+            return Ok(byte == value);
+        }
+        let mut sbytes = [SByte::Concrete(0); 8];
+        sbytes[0] = byte;
+        let equal = self.memory.constraints.add_binop_constraint(
+            mir::BinOp::Eq, PrimVal::Abstract(sbytes), PrimVal::Bytes(value as u128),
+            PrimValKind::U8)?;
+        let kind = PrimValKind::Bool;
+        match self.memory.constraints.unique_value(equal, kind, 1) {
+            Ok(equal) => Ok(equal == 1),
+            Err(candidates) => Err(EvalError::NeedsConcreteValue {
+                value: equal,
+                kind,
+                candidates,
+                // Cannot happen, a comparison is either true or false.
+                otherwise: Box::new(EvalError::Unimplemented(
+                    "comparison neither true nor false".to_string())),
+            }),
+        }
+    }
+
+    /// The byte `offset` bytes after those the program has read, which stays pending until it
+    /// is consumed.
+    fn peek_stdin(&mut self, offset: u64) -> SByte {
+        while self.stdin.pending.len() as u64 <= offset {
+            let byte = self.memory.constraints.fresh_stdin_byte();
+            self.stdin.pending.push(byte);
+        }
+        self.stdin.pending[offset as usize]
+    }
+
+    /// Takes the next `len` bytes of stdin.
+    fn consume_stdin(&mut self, len: u64) -> Vec<SByte> {
+        if len > 0 {
+            self.peek_stdin(len - 1);
+        }
+        self.stdin.read += len;
+        self.stdin.pending.drain(..len as usize).collect()
+    }
+
+    /// Appends `bytes` to the `Vec<u8>` of type `ty` at `vec`, growing it if needed.
+    fn append_to_byte_vec(
+        &mut self,
+        vec: MemoryPointer,
+        ty: Ty<'tcx>,
+        bytes: &[SByte],
+    ) -> EvalResult<'tcx> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        let (ptr_offset, cap_offset, len_offset) = self.byte_vec_offsets(ty)?;
+        let layout = self.memory.layout;
+        let ptr_field = vec.offset(ptr_offset, layout)?;
+        let cap_field = vec.offset(cap_offset, layout)?;
+        let len_field = vec.offset(len_offset, layout)?;
+
+        let len = self.memory.read_usize(len_field)?;
+        let cap = self.memory.read_usize(cap_field)?;
+        let new_len = len + bytes.len() as u64;
+        if new_len > cap {
+            let data = if cap == 0 {
+                self.memory.allocate(new_len, 1)?
+            } else {
+                let data = self.memory.read_ptr(ptr_field)?.to_ptr()?;
+                self.memory.reallocate(data, new_len, 1)?
+            };
+            self.memory.write_ptr(ptr_field, data)?;
+            self.memory.write_usize(cap_field, new_len)?;
+        }
+        let data = self.memory.read_ptr(ptr_field)?.to_ptr()?;
+        self.memory.write_sbytes(data.offset(len, layout)?, bytes)?;
+        self.memory.write_usize(len_field, new_len)
+    }
+
+    /// Writes `Ok(num_bytes)` to `dest`, an `io::Result<usize>` of type `dest_ty`.
+    fn write_io_ok(
+        &mut self,
+        dest: Place<'tcx>,
+        dest_ty: Ty<'tcx>,
+        num_bytes: u64,
+    ) -> EvalResult<'tcx> {
+        let layout = self.layout_of(dest_ty)?;
+        let dest = self.force_allocation(dest)?;
+        self.write_discriminant_value(dest_ty, dest, 0)?; // Ok
+        let ok = self.place_downcast(dest, 0)?;
+        let (ok, ok_layout) = self.place_field(ok, mir::Field::new(0), layout)?;
+        self.write_primval(ok, PrimVal::Bytes(num_bytes as u128), ok_layout.ty)
+    }
+
+    /// Writes `Err(error)` to `dest`, an `io::Result<usize>` of type `dest_ty`, where `error`
+    /// is an `io::Error` of the `io::ErrorKind` named `kind`.
+    fn write_io_error(
+        &mut self,
+        dest: Place<'tcx>,
+        dest_ty: Ty<'tcx>,
+        kind: &str,
+    ) -> EvalResult<'tcx> {
+        // Payloads come before discriminants, which may live inside them.
+        let layout = self.layout_of(dest_ty)?;
+        let dest = self.force_allocation(dest)?;
+        let err = self.place_downcast(dest, 1)?;
+        let (error, error_layout) = self.place_field(err, mir::Field::new(0), layout)?;
+        // An `io::Error` holds nothing but its `Repr`, which is `Simple(kind)` here.
+        let (repr, repr_layout) = self.place_field(error, mir::Field::new(0), error_layout)?;
+        let simple = variant_index(repr_layout.ty, "Simple");
+        let simple_place = self.place_downcast(repr, simple)?;
+        let (kind_place, kind_layout) =
+            self.place_field(simple_place, mir::Field::new(0), repr_layout)?;
+        let kind = variant_index(kind_layout.ty, kind);
+        self.write_discriminant_value(kind_layout.ty, kind_place, kind)?;
+        self.write_discriminant_value(repr_layout.ty, repr, simple)?;
+        self.write_discriminant_value(dest_ty, dest, 1) // Err
+    }
+
+    fn is_stdin_lock(&self, ty: Ty<'tcx>) -> bool {
+        match ty.sty {
+            ty::TyAdt(adt, _) => self.tcx.item_path_str(adt.did) == "std::io::StdinLock",
+            _ => false,
+        }
+    }
+}
+
+/// The index of the variant `name` of the enum `ty`.
+fn variant_index(ty: Ty, name: &str) -> usize {
+    let adt = ty.ty_adt_def().unwrap_or_else(|| bug!("{:?} is not an enum", ty));
+    adt.variants.iter().position(|variant| &*variant.name.as_str() == name)
+        .unwrap_or_else(|| bug!("{:?} has no variant `{}`", ty, name))
+}
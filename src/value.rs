@@ -35,7 +35,7 @@ pub(super) fn bytes_to_bool(n: u128) -> bool {
 /// For optimization of a few very common cases, there is also a representation for a pair of
 /// primitive values (`ByValPair`). It allows Miri to avoid making allocations for checked binary
 /// operations and fat pointers. This idea was taken from rustc's trans.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Value {
     ByRef(MemoryPointer),
     ByVal(PrimVal),
@@ -46,7 +46,7 @@ pub enum Value {
 /// `memory::Allocation`. It is in many ways like a small chunk of a `Allocation`, up to 8 bytes in
 /// size. Like a range of bytes in an `Allocation`, a `PrimVal` can either represent the raw bytes
 /// of a simple value, a pointer into another `Allocation`, or be undefined.
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum PrimVal {
     /// The raw bytes of a simple value.
     Bytes(u128),
@@ -64,7 +64,7 @@ pub enum PrimVal {
     Undef,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum PrimValKind {
     I8, I16, I32, I64, I128,
     U8, U16, U32, U64, U128,
@@ -113,7 +113,7 @@ impl<'a, 'tcx: 'a> Value {
             ByValPair(ptr, val) => {
                 Ok((ptr, val))
             },
-            _ => unimplemented!(),
+            ByVal(_) => bug!("expected ptr and length, got {:?}", self),
         }
     }
 }
@@ -153,7 +153,8 @@ impl<'tcx> PrimVal {
     pub fn to_bytes(self) -> EvalResult<'tcx, u128> {
         match self {
             PrimVal::Bytes(b) => Ok(b),
-            PrimVal::Abstract(_) => unimplemented!(),
+            PrimVal::Abstract(_) => Err(EvalError::Unimplemented(
+                "using an input-dependent value where a concrete one is required".to_string())),
             PrimVal::Ptr(_) => Err(EvalError::ReadBytesAsPointer),
             PrimVal::Undef => Err(EvalError::ReadUndefBytes),
         }
@@ -162,7 +163,8 @@ impl<'tcx> PrimVal {
     pub fn to_ptr(self) -> EvalResult<'tcx, MemoryPointer> {
         match self {
             PrimVal::Bytes(_) => Err(EvalError::ReadBytesAsPointer),
-            PrimVal::Abstract(_) => unimplemented!(),
+            PrimVal::Abstract(_) => Err(EvalError::Unimplemented(
+                "using an input-dependent integer as a pointer".to_string())),
             PrimVal::Ptr(p) => Ok(p),
             PrimVal::Undef => Err(EvalError::ReadUndefBytes),
         }
@@ -239,7 +241,8 @@ impl<'tcx> PrimVal {
             PrimVal::Bytes(b) => Ok(b == 0),
             PrimVal::Ptr(_) => Ok(false),
             PrimVal::Undef => Err(EvalError::ReadUndefBytes),
-            PrimVal::Abstract(_) => unimplemented!(),
+            PrimVal::Abstract(_) => Err(EvalError::Unimplemented(
+                "comparing an input-dependent integer against null".to_string())),
         }
     }
 
@@ -251,7 +254,8 @@ impl<'tcx> PrimVal {
             },
             PrimVal::Ptr(ptr) => ptr.signed_offset(i, layout).map(PrimVal::Ptr),
             PrimVal::Undef => Err(EvalError::ReadUndefBytes),
-            PrimVal::Abstract(_) => unimplemented!(),
+            PrimVal::Abstract(_) => Err(EvalError::Unimplemented(
+                "offsetting an input-dependent integer".to_string())),
         }
     }
 
@@ -263,7 +267,8 @@ impl<'tcx> PrimVal {
             },
             PrimVal::Ptr(ptr) => ptr.offset(i, layout).map(PrimVal::Ptr),
             PrimVal::Undef => Err(EvalError::ReadUndefBytes),
-            PrimVal::Abstract(_) => unimplemented!(),
+            PrimVal::Abstract(_) => Err(EvalError::Unimplemented(
+                "offsetting an input-dependent integer".to_string())),
         }
     }
 
@@ -273,9 +278,10 @@ impl<'tcx> PrimVal {
                 assert_eq!(b as u64 as u128, b);
                 Ok(PrimVal::Bytes(wrapping_signed_offset(b as u64, i, layout) as u128))
             },
-            PrimVal::Ptr(ptr) => Ok(PrimVal::Ptr(ptr.wrapping_signed_offset(i, layout))),
+            PrimVal::Ptr(ptr) => ptr.wrapping_signed_offset(i, layout).map(PrimVal::Ptr),
             PrimVal::Undef => Err(EvalError::ReadUndefBytes),
-            PrimVal::Abstract(_) => unimplemented!(),
+            PrimVal::Abstract(_) => Err(EvalError::Unimplemented(
+                "offsetting an input-dependent integer".to_string())),
         }
     }
 }
@@ -342,7 +348,8 @@ impl PrimValKind {
             I64 | U64 => 8,
             F64 => 8,
             Char => 4,
-            _ => unimplemented!(),
+            I128 | U128 => 16,
+            Ptr | FnPtr => bug!("the size of a pointer depends on the target"),
         }
     }
 
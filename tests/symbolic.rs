@@ -2,33 +2,52 @@ extern crate seer;
 
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::io::Write;
 
 fn expect_single_panic(filename: &str, expected_result: Vec<u8>) {
     expect_panics(filename, vec![expected_result]);
 }
 
-fn expect_panics(filename: &str, mut expected_results: Vec<Vec<u8>>) {
+fn expect_panics(filename: &str, expected_results: Vec<Vec<u8>>) {
+    expect_panics_with_config(filename, |_| {}, expected_results);
+}
+
+fn expect_panics_with_search(filename: &str,
+                             strategy: ::seer::SearchStrategyKind,
+                             expected_results: Vec<Vec<u8>>) {
+    expect_panics_with_config(
+        filename,
+        |config| { config.search_strategy(strategy); },
+        expected_results);
+}
+
+fn expect_panics_with_config<F>(filename: &str,
+                                configure: F,
+                                mut expected_results: Vec<Vec<u8>>)
+    where F: FnOnce(&mut ::seer::ExecutionConfig)
+{
     let found = Rc::new(RefCell::new(Vec::new()));
     let found1 = found.clone();
     let consumer = move |complete| {
         match complete {
-            ::seer::ExecutionComplete { result: Err(::seer::StaticEvalError::Panic),
-                                        mut input } => {
+            ::seer::ExecutionComplete { result: Err(::seer::StaticEvalError::Panic { .. }),
+                                        mut input, .. } => {
                 let stdin = ::std::mem::replace(&mut input[0].assignments, Vec::new());
                 found1.borrow_mut().push(stdin);
                 true
             }
-            ::seer::ExecutionComplete { result: Ok(()), input: _ } => true,
-            ::seer::ExecutionComplete { result: Err(e), input } => {
+            ::seer::ExecutionComplete { result: Ok(()), .. } => true,
+            ::seer::ExecutionComplete { result: Err(e), input, .. } => {
                 panic!("unexpected error {:?} with input {:?}", e, input)
             }
         }
     };
 
     let args = vec!["seer".to_string(), filename.to_string()];
-    ::seer::ExecutionConfig::new()
-        .consumer(consumer)
-        .run(args);
+    let mut config = ::seer::ExecutionConfig::new();
+    config.consumer(consumer);
+    configure(&mut config);
+    config.run(args);
 
     let mut found = ::std::mem::replace(&mut *found.borrow_mut(), Vec::new());
     found.sort();
@@ -37,6 +56,34 @@ fn expect_panics(filename: &str, mut expected_results: Vec<Vec<u8>>) {
     assert_eq!(found, expected_results);
 }
 
+fn expect_single_error(filename: &str, expected_error: &str, expected_result: Vec<u8>) {
+    assert_eq!(single_error(filename), (expected_error.to_string(), expected_result));
+}
+
+/// Returns the `Debug` form of the only error found in `filename` and its stdin.
+fn single_error(filename: &str) -> (String, Vec<u8>) {
+    let found = Rc::new(RefCell::new(Vec::new()));
+    let found1 = found.clone();
+    let consumer = move |complete| {
+        match complete {
+            ::seer::ExecutionComplete { result: Ok(()), .. } => true,
+            ::seer::ExecutionComplete { result: Err(e), mut input, .. } => {
+                let stdin = ::std::mem::replace(&mut input[0].assignments, Vec::new());
+                found1.borrow_mut().push((format!("{:?}", e), stdin));
+                true
+            }
+        }
+    };
+
+    let args = vec!["seer".to_string(), filename.to_string()];
+    let mut config = ::seer::ExecutionConfig::new();
+    config.consumer(consumer);
+    config.run(args);
+
+    let mut found = ::std::mem::replace(&mut *found.borrow_mut(), Vec::new());
+    assert_eq!(found.len(), 1, "expected a single error, found {:?}", found);
+    found.pop().unwrap()
+}
 
 #[test]
 fn symbolic_simple() {
@@ -55,6 +102,86 @@ fn symbolic_comparisons() {
     expect_single_panic("tests/symbolic/comparisons.rs", vec![17, 18, 38, 37, 101]);
 }
 
+#[test]
+fn symbolic_comparisons_breadth_first() {
+    expect_panics_with_search(
+        "tests/symbolic/comparisons.rs",
+        ::seer::SearchStrategyKind::BreadthFirst,
+        vec![vec![17, 18, 38, 37, 101]]);
+}
+
+#[test]
+fn symbolic_comparisons_random_path() {
+    expect_panics_with_search(
+        "tests/symbolic/comparisons.rs",
+        ::seer::SearchStrategyKind::RandomPath { seed: 7 },
+        vec![vec![17, 18, 38, 37, 101]]);
+}
+
+#[test]
+fn symbolic_comparisons_shortest_path_first() {
+    expect_panics_with_search(
+        "tests/symbolic/comparisons.rs",
+        ::seer::SearchStrategyKind::ShortestPathFirst,
+        vec![vec![17, 18, 38, 37, 101]]);
+}
+
+#[test]
+fn symbolic_comparisons_coverage_guided() {
+    expect_panics_with_search(
+        "tests/symbolic/comparisons.rs",
+        ::seer::SearchStrategyKind::CoverageGuided,
+        vec![vec![17, 18, 38, 37, 101]]);
+}
+
+#[test]
+fn symbolic_coverage_report() {
+    let reports = Rc::new(RefCell::new(Vec::new()));
+    let reports1 = reports.clone();
+
+    let args = vec!["seer".to_string(), "tests/symbolic/simple.rs".to_string()];
+    ::seer::ExecutionConfig::new()
+        .consumer(|_| true)
+        .coverage_consumer(move |report| reports1.borrow_mut().push(report))
+        .run(args);
+
+    let reports = reports.borrow();
+    assert_eq!(reports.len(), 1);
+    let main = reports[0].functions.iter()
+        .find(|f| f.name.ends_with("main"))
+        .expect("main was not covered");
+    assert!(main.total_blocks > 0);
+    assert!(main.uncovered_blocks.len() < main.total_blocks);
+}
+
+fn count_paths<F>(filename: &str, configure: F) -> usize
+    where F: FnOnce(&mut ::seer::ExecutionConfig)
+{
+    let count = Rc::new(RefCell::new(0));
+    let count1 = count.clone();
+    let args = vec!["seer".to_string(), filename.to_string()];
+    let mut config = ::seer::ExecutionConfig::new();
+    config.consumer(move |_| { *count1.borrow_mut() += 1; true });
+    configure(&mut config);
+    config.run(args);
+    let paths = *count.borrow();
+    paths
+}
+
+#[test]
+fn symbolic_merge_states() {
+    expect_panics_with_config(
+        "tests/symbolic/merge.rs",
+        |config| { config.merge_states(true); },
+        vec![vec![3, 1, 4, 1]]);
+
+    // Without merging, every combination of the four branches is a path of its own.
+    let merged = count_paths("tests/symbolic/merge.rs", |config| { config.merge_states(true); });
+    let unmerged = count_paths("tests/symbolic/merge.rs", |_| {});
+    assert_eq!(unmerged, 16);
+    assert!(merged < unmerged, "{} paths with merging, {} without", merged, unmerged);
+}
+
 #[test]
 fn symbolic_write_mem() {
     expect_single_panic("tests/symbolic/write_mem.rs", vec![7, 3, 21, 21]);
@@ -208,3 +335,631 @@ fn symbolic_count_ones() {
         vec![255]);
 
 }
+
+#[test]
+fn symbolic_overflow_neg() {
+    expect_single_error(
+        "tests/symbolic/overflow_neg.rs",
+        "OverflowNeg",
+        vec![128]);
+}
+
+#[test]
+fn symbolic_overflow_mul() {
+    let (error, stdin) = single_error("tests/symbolic/overflow_mul.rs");
+    assert_eq!(error, "Overflow(Mul)");
+    assert!(stdin[0] >= 128, "{:?} does not overflow", stdin);
+}
+
+#[test]
+fn symbolic_overflow_shl() {
+    let (error, stdin) = single_error("tests/symbolic/overflow_shl.rs");
+    assert_eq!(error, "Overflow(Shl)");
+    assert_eq!(stdin[0], 0);
+    assert!(stdin[1..].iter().any(|&b| b != 0), "{:?} does not overflow", stdin);
+}
+
+#[test]
+fn symbolic_division_by_zero() {
+    expect_single_error(
+        "tests/symbolic/div_by_zero.rs",
+        "DivisionByZero",
+        vec![0]);
+}
+
+#[test]
+fn symbolic_remainder_by_zero() {
+    expect_single_error(
+        "tests/symbolic/rem_by_zero.rs",
+        "RemainderByZero",
+        vec![0]);
+}
+
+#[test]
+fn symbolic_alloc() {
+    expect_single_panic(
+        "tests/symbolic/symbolic_alloc.rs",
+        vec![5]);
+}
+
+#[test]
+fn symbolic_pointer_table() {
+    expect_single_panic(
+        "tests/symbolic/pointer_table.rs",
+        vec![2]);
+}
+
+#[test]
+fn symbolic_pointer_table_one_alloc() {
+    expect_single_panic(
+        "tests/symbolic/pointer_table_one_alloc.rs",
+        vec![2]);
+
+    // Every entry points into `values`, so reading the entry does not fork: the index is
+    // either out of bounds, or selects an entry that is 30 or not.
+    let paths = count_paths("tests/symbolic/pointer_table_one_alloc.rs", |_| {});
+    assert_eq!(paths, 3);
+}
+
+#[test]
+fn symbolic_abstract_offset_u32() {
+    expect_single_panic(
+        "tests/symbolic/abstract_offset_u32.rs",
+        vec![1]);
+}
+
+#[test]
+fn symbolic_engine_limitation() {
+    let panics = Rc::new(RefCell::new(Vec::new()));
+    let panics1 = panics.clone();
+    let limitations = Rc::new(RefCell::new(Vec::new()));
+    let limitations1 = limitations.clone();
+    let consumer = move |complete| {
+        match complete {
+            ::seer::ExecutionComplete { result: Err(::seer::StaticEvalError::Panic { .. }),
+                                        mut input, .. } => {
+                let stdin = ::std::mem::replace(&mut input[0].assignments, Vec::new());
+                panics1.borrow_mut().push(stdin);
+                true
+            }
+            ::seer::ExecutionComplete { result: Ok(()), .. } => true,
+            ::seer::ExecutionComplete { result: Err(ref e), ref input, .. }
+                if e.is_engine_limitation() =>
+            {
+                limitations1.borrow_mut().push(input[0].assignments[0]);
+                // The other paths must still be explored.
+                true
+            }
+            ::seer::ExecutionComplete { result: Err(e), input, .. } => {
+                panic!("unexpected error {:?} with input {:?}", e, input)
+            }
+        }
+    };
+
+    let args = vec!["seer".to_string(), "tests/symbolic/engine_limitation.rs".to_string()];
+    ::seer::ExecutionConfig::new()
+        .consumer(consumer)
+        .run(args);
+
+    assert_eq!(*panics.borrow(), vec![vec![4, 42]]);
+    assert_eq!(*limitations.borrow(), vec![3]);
+}
+
+#[test]
+fn symbolic_entry_args() {
+    let found = Rc::new(RefCell::new(Vec::new()));
+    let found1 = found.clone();
+    let consumer = move |complete| {
+        match complete {
+            ::seer::ExecutionComplete {
+                result: Err(::seer::StaticEvalError::Panic { .. }), input, ..
+            } => {
+                let vars: Vec<(String, Vec<u8>)> =
+                    input.into_iter().map(|v| (v.label, v.assignments)).collect();
+                found1.borrow_mut().push(vars);
+                true
+            }
+            ::seer::ExecutionComplete { result: Ok(()), .. } => true,
+            ::seer::ExecutionComplete { result: Err(e), input, .. } => {
+                panic!("unexpected error {:?} with input {:?}", e, input)
+            }
+        }
+    };
+
+    let args = vec!["seer".to_string(), "tests/symbolic/entry_args.rs".to_string()];
+    ::seer::ExecutionConfig::new()
+        .consumer(consumer)
+        .entry("check")
+        .entry_slice_len(2)
+        .run(args);
+
+    let found = ::std::mem::replace(&mut *found.borrow_mut(), Vec::new());
+    assert_eq!(found, vec![vec![
+        ("stdin".to_string(), vec![]),
+        ("x".to_string(), vec![0xad, 0xde, 0, 0]),
+        ("data".to_string(), vec![1, 7]),
+        ("*flag".to_string(), vec![3]),
+    ]]);
+}
+
+#[test]
+fn symbolic_entry_no_leak() {
+    let results = Rc::new(RefCell::new(Vec::new()));
+    let results1 = results.clone();
+    let args = vec!["seer".to_string(), "tests/symbolic/entry_return.rs".to_string()];
+    // A leak is reported as an error of the compiler session, which makes `run` panic.
+    ::seer::ExecutionConfig::new()
+        .consumer(move |complete| {
+            results1.borrow_mut().push(format!("{:?}", complete.result));
+            true
+        })
+        .entry("sum")
+        .entry_slice_len(2)
+        .run(args);
+
+    assert_eq!(*results.borrow(), vec!["Ok(())".to_string()]);
+}
+
+#[test]
+fn symbolic_run_tests() {
+    let summaries = Rc::new(RefCell::new(Vec::new()));
+    let summaries1 = summaries.clone();
+
+    let args = vec![
+        "seer".to_string(),
+        "tests/symbolic/unit_tests.rs".to_string(),
+        "--test".to_string(),
+    ];
+    ::seer::ExecutionConfig::new()
+        .tests(true)
+        .test_consumer(move |summary| summaries1.borrow_mut().push(summary))
+        .run(args);
+
+    let summaries = summaries.borrow();
+    let names: Vec<&str> = summaries.iter().map(|s| &s.name[..]).collect();
+    assert_eq!(names, vec!["tests::always_passes", "tests::finds_magic_byte"]);
+
+    assert!(summaries[0].errors.is_empty());
+    assert_eq!(summaries[0].status, ::seer::ExplorationStatus::Finished);
+
+    let magic = &summaries[1];
+    assert_eq!(magic.status, ::seer::ExplorationStatus::Finished);
+    assert!(magic.paths_explored >= 2);
+    assert_eq!(magic.errors.len(), 1);
+    match magic.errors[0] {
+        ::seer::ExecutionComplete {
+            result: Err(::seer::StaticEvalError::Panic { .. }), ref input, ..
+        } => {
+            assert_eq!(input[0].assignments, vec![7]);
+        }
+        ref other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn symbolic_max_pending_states() {
+    let summaries = Rc::new(RefCell::new(Vec::new()));
+    let summaries1 = summaries.clone();
+
+    let args = vec![
+        "seer".to_string(),
+        "tests/symbolic/unit_tests.rs".to_string(),
+        "--test".to_string(),
+    ];
+    // Only one of the two branches on the magic byte fits into the queue.
+    ::seer::ExecutionConfig::new()
+        .tests(true)
+        .max_pending_states(1)
+        .test_consumer(move |summary| summaries1.borrow_mut().push(summary))
+        .run(args);
+
+    let summaries = summaries.borrow();
+    let statuses: Vec<(&str, ::seer::ExplorationStatus)> =
+        summaries.iter().map(|s| (&s.name[..], s.status)).collect();
+    assert_eq!(statuses, vec![
+        ("tests::always_passes", ::seer::ExplorationStatus::Finished),
+        ("tests::finds_magic_byte", ::seer::ExplorationStatus::LimitReached),
+    ]);
+}
+
+/// Collects what seer writes, so that a test can look at it after the run.
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> ::std::io::Result<usize> {
+        self.0.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> ::std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn symbolic_json_output() {
+    let output = Rc::new(RefCell::new(Vec::new()));
+
+    let args = vec!["seer".to_string(), "tests/symbolic/simple.rs".to_string()];
+    ::seer::ExecutionConfig::new()
+        .json_output(SharedBuffer(output.clone()))
+        .run(args);
+
+    let output = String::from_utf8(output.borrow().clone()).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert!(lines.len() >= 2);
+    assert!(lines.iter().all(|line| line.starts_with("{\"inputs\":[") && line.ends_with("]}")));
+
+    let errors: Vec<&&str> = lines.iter().filter(|line| line.contains("\"result\":\"error\"")).collect();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("{\"label\":\"stdin\",\"bytes\":[43],\"assignments_str\":null}"));
+    assert!(errors[0].contains("\"error\":{\"variant\":\"Panic\",\"fields\":{\"message\":\"explicit panic\""));
+    assert!(errors[0].contains("\"stack\":[{\"function\":"));
+    assert!(errors[0].contains("main\",\"file\":"));
+}
+
+#[test]
+fn symbolic_corpus_dir() {
+    let dir = ::std::env::temp_dir().join("seer-symbolic-corpus-dir");
+    let _ = ::std::fs::remove_dir_all(&dir);
+
+    let args = vec!["seer".to_string(), "tests/symbolic/simple.rs".to_string()];
+    ::seer::ExecutionConfig::new()
+        .corpus_dir(dir.clone())
+        .corpus_only_errors(true)
+        .run(args);
+
+    let entries: Vec<_> = ::std::fs::read_dir(&dir).unwrap().map(|e| e.unwrap()).collect();
+    assert_eq!(entries.len(), 1);
+    let name = entries[0].file_name().into_string().unwrap();
+    assert_eq!(name.len(), 40);
+    assert!(name.chars().all(|c| c.is_digit(16)));
+    assert_eq!(::std::fs::read(entries[0].path()).unwrap(), vec![43]);
+
+    ::std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn symbolic_regression_tests() {
+    let path = ::std::env::temp_dir().join("seer-symbolic-regression-tests.rs");
+    let _ = ::std::fs::remove_file(&path);
+
+    let args = vec!["seer".to_string(), "tests/symbolic/simple.rs".to_string()];
+    ::seer::ExecutionConfig::new()
+        .regression_tests(path.clone(), "simple")
+        .run(args);
+
+    let tests = ::std::fs::read_to_string(&path).unwrap();
+    assert!(tests.contains("path.push(\"simple\");"));
+    assert_eq!(tests.matches("#[test]").count(), 1);
+    assert!(tests.contains("fn seer_regression_0() {"));
+    assert!(tests.contains("seer_run(&[], &[], &[43], \"\");"));
+    assert!(tests.contains("stderr.contains(\"explicit panic\")"));
+
+    ::std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn symbolic_panic_message() {
+    let found = Rc::new(RefCell::new(Vec::new()));
+    let found1 = found.clone();
+    let consumer = move |complete| {
+        match complete {
+            ::seer::ExecutionComplete {
+                result: Err(::seer::StaticEvalError::Panic { message, location }),
+                input,
+                ..
+            } => {
+                found1.borrow_mut().push((input[0].assignments[0], message, location));
+                true
+            }
+            ::seer::ExecutionComplete { result: Ok(()), .. } => true,
+            ::seer::ExecutionComplete { result: Err(e), input, .. } => {
+                panic!("unexpected error {:?} with input {:?}", e, input)
+            }
+        }
+    };
+
+    let args = vec!["seer".to_string(), "tests/symbolic/panic_message.rs".to_string()];
+    ::seer::ExecutionConfig::new()
+        .consumer(consumer)
+        .run(args);
+
+    let mut found = ::std::mem::replace(&mut *found.borrow_mut(), Vec::new());
+    found.sort_by_key(|&(first_byte, _, _)| first_byte);
+    let messages: Vec<(u8, Option<String>)> =
+        found.iter().map(|&(first_byte, ref message, _)| (first_byte, message.clone())).collect();
+    assert_eq!(messages, vec![
+        (1, Some("invariant violated".to_string())),
+        (2, Some("called `Option::unwrap()` on a `None` value".to_string())),
+        (3, Some("bad byte {..}".to_string())),
+        (4, Some("got 7, expected \"seven\"".to_string())),
+    ]);
+    assert_eq!(found[0].2, Some(::seer::PanicLocation {
+        file: "tests/symbolic/panic_message.rs".to_string(),
+        line: 9,
+        column: 14,
+    }));
+}
+
+#[test]
+fn symbolic_backtrace() {
+    let found = Rc::new(RefCell::new(None));
+    let found1 = found.clone();
+    let consumer = move |complete: ::seer::ExecutionComplete| {
+        if complete.result.is_err() {
+            *found1.borrow_mut() = Some(complete.backtrace);
+        }
+        true
+    };
+
+    let args = vec!["seer".to_string(), "tests/symbolic/backtrace.rs".to_string()];
+    ::seer::ExecutionConfig::new()
+        .consumer(consumer)
+        .run(args);
+
+    let backtrace = found.borrow_mut().take().expect("no path ended in an error");
+    // Frames inside the panic machinery of the standard library come first.
+    let frames: Vec<(String, usize, usize)> = backtrace.into_iter()
+        .filter(|frame| frame.file == "tests/symbolic/backtrace.rs")
+        .map(|frame| (frame.function, frame.line, frame.column))
+        .collect();
+    assert_eq!(frames, vec![
+        ("check".to_string(), 5, 9),
+        ("main".to_string(), 13, 5),
+    ]);
+}
+
+#[test]
+fn symbolic_deduplicate_errors() {
+    let reported = Rc::new(RefCell::new(0));
+    let reported1 = reported.clone();
+    let consumer = move |complete: ::seer::ExecutionComplete| {
+        if complete.result.is_err() {
+            *reported1.borrow_mut() += 1;
+        }
+        true
+    };
+    let findings = Rc::new(RefCell::new(Vec::new()));
+    let findings1 = findings.clone();
+    let output = Rc::new(RefCell::new(Vec::new()));
+
+    let args = vec!["seer".to_string(), "tests/symbolic/dedup.rs".to_string()];
+    ::seer::ExecutionConfig::new()
+        .consumer(consumer)
+        .deduplicate_errors(true)
+        .findings_consumer(move |found| *findings1.borrow_mut() = found)
+        .json_output(SharedBuffer(output.clone()))
+        .run(args);
+
+    assert_eq!(*reported.borrow(), 2);
+    // The JSON output still gets every path.
+    let output = String::from_utf8(output.borrow().clone()).unwrap();
+    assert_eq!(output.lines().filter(|line| line.contains("\"result\":\"error\"")).count(), 8);
+    let mut found: Vec<(u8, usize, usize)> = findings.borrow().iter()
+        .map(|finding| {
+            let location = finding.location.as_ref().expect("finding without location");
+            assert_eq!(location.file, "tests/symbolic/dedup.rs");
+            (finding.example.input[0].assignments[1], location.line, finding.occurrences)
+        })
+        .collect();
+    found.sort();
+    assert_eq!(found, vec![(9, 18, 4), (10, 21, 4)]);
+}
+
+#[test]
+fn symbolic_max_paths() {
+    let paths = Rc::new(RefCell::new(0));
+    let paths1 = paths.clone();
+    let consumer = move |_| {
+        *paths1.borrow_mut() += 1;
+        true
+    };
+
+    let args = vec!["seer".to_string(), "tests/symbolic/dedup.rs".to_string()];
+    ::seer::ExecutionConfig::new()
+        .consumer(consumer)
+        .max_paths(3)
+        .run(args);
+
+    assert_eq!(*paths.borrow(), 3);
+}
+
+#[test]
+fn symbolic_step_limit() {
+    let results = Rc::new(RefCell::new(Vec::new()));
+    let results1 = results.clone();
+    let consumer = move |complete: ::seer::ExecutionComplete| {
+        results1.borrow_mut().push(complete.result);
+        true
+    };
+
+    let args = vec!["seer".to_string(), "tests/symbolic/long_loop.rs".to_string()];
+    ::seer::ExecutionConfig::new()
+        .consumer(consumer)
+        .step_limit(1000)
+        .run(args);
+
+    let results = results.borrow();
+    assert_eq!(results.len(), 1);
+    match results[0] {
+        Err(::seer::StaticEvalError::ExecutionTimeLimitReached) => {}
+        ref other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn symbolic_program_args() {
+    let found = Rc::new(RefCell::new(Vec::new()));
+    let found1 = found.clone();
+    let consumer = move |complete| {
+        match complete {
+            ::seer::ExecutionComplete {
+                result: Err(::seer::StaticEvalError::Panic { .. }), input, ..
+            } => {
+                let args: Vec<(String, Option<String>)> = input.into_iter()
+                    .filter(|v| v.label.starts_with("argv["))
+                    .map(|v| (v.label, v.assignments_str))
+                    .collect();
+                found1.borrow_mut().push(args);
+                true
+            }
+            ::seer::ExecutionComplete { result: Ok(()), .. } => true,
+            ::seer::ExecutionComplete { result: Err(e), input, .. } => {
+                panic!("unexpected error {:?} with input {:?}", e, input)
+            }
+        }
+    };
+
+    let args = vec!["seer".to_string(), "tests/symbolic/args.rs".to_string()];
+    ::seer::ExecutionConfig::new()
+        .consumer(consumer)
+        .symbolic_args(1)
+        .run(args);
+
+    let found = ::std::mem::replace(&mut *found.borrow_mut(), Vec::new());
+    assert_eq!(found, vec![vec![("argv[1]".to_string(), Some("--magic".to_string()))]]);
+}
+
+#[test]
+fn symbolic_env_var() {
+    let found = Rc::new(RefCell::new(Vec::new()));
+    let found1 = found.clone();
+    let consumer = move |complete| {
+        match complete {
+            ::seer::ExecutionComplete {
+                result: Err(::seer::StaticEvalError::Panic { .. }), input, ..
+            } => {
+                let vars: Vec<(String, Option<String>)> = input.into_iter()
+                    .filter(|v| v.label.starts_with("env["))
+                    .map(|v| (v.label, v.assignments_str))
+                    .collect();
+                found1.borrow_mut().push(vars);
+                true
+            }
+            ::seer::ExecutionComplete { result: Ok(()), .. } => true,
+            ::seer::ExecutionComplete { result: Err(e), input, .. } => {
+                panic!("unexpected error {:?} with input {:?}", e, input)
+            }
+        }
+    };
+
+    let args = vec!["seer".to_string(), "tests/symbolic/env_var.rs".to_string()];
+    ::seer::ExecutionConfig::new()
+        .consumer(consumer)
+        .symbolic_env_var("SEER_MODE")
+        .run(args);
+
+    let mut found = ::std::mem::replace(&mut *found.borrow_mut(), Vec::new());
+    found.sort();
+    assert_eq!(found, vec![
+        vec![("env[SEER_MODE]".to_string(), None)],
+        vec![("env[SEER_MODE]".to_string(), Some("debug".to_string()))],
+    ]);
+}
+
+#[test]
+fn symbolic_file() {
+    let found = Rc::new(RefCell::new(Vec::new()));
+    let found1 = found.clone();
+    let consumer = move |complete| {
+        match complete {
+            ::seer::ExecutionComplete {
+                result: Err(::seer::StaticEvalError::Panic { .. }), input, ..
+            } => {
+                let file = input.into_iter().find(|v| v.label == "file[config.txt]").unwrap();
+                found1.borrow_mut().push(file.assignments);
+                true
+            }
+            ::seer::ExecutionComplete { result: Ok(()), .. } => true,
+            ::seer::ExecutionComplete { result: Err(e), input, .. } => {
+                panic!("unexpected error {:?} with input {:?}", e, input)
+            }
+        }
+    };
+
+    let args = vec!["seer".to_string(), "tests/symbolic/read_file.rs".to_string()];
+    ::seer::ExecutionConfig::new()
+        .consumer(consumer)
+        .symbolic_file("config.txt", 8)
+        .run(args);
+
+    // One panic for every length the file can have and still start with "debug".
+    let found = ::std::mem::replace(&mut *found.borrow_mut(), Vec::new());
+    let mut lens: Vec<usize> = found.iter().map(|contents| contents.len()).collect();
+    lens.sort();
+    assert_eq!(lens, vec![5, 6, 7, 8]);
+    assert!(found.iter().all(|contents| contents.starts_with(b"debug")));
+}
+
+#[test]
+fn symbolic_stdin_lines() {
+    let found = Rc::new(RefCell::new(Vec::new()));
+    let found1 = found.clone();
+    let consumer = move |complete| {
+        match complete {
+            ::seer::ExecutionComplete {
+                result: Err(::seer::StaticEvalError::Panic { .. }), input, ..
+            } => {
+                let stdin = input.into_iter().find(|v| v.label == "stdin").unwrap();
+                found1.borrow_mut().push(stdin.assignments);
+                true
+            }
+            ::seer::ExecutionComplete { result: Ok(()), .. } => true,
+            ::seer::ExecutionComplete { result: Err(e), input, .. } => {
+                panic!("unexpected error {:?} with input {:?}", e, input)
+            }
+        }
+    };
+
+    let args = vec!["seer".to_string(), "tests/symbolic/read_lines.rs".to_string()];
+    ::seer::ExecutionConfig::new()
+        .consumer(consumer)
+        .stdin_len(3)
+        .run(args);
+
+    // Every stdin of at most three bytes with a line that reads "go".
+    let mut found = ::std::mem::replace(&mut *found.borrow_mut(), Vec::new());
+    found.sort();
+    // `lines` only strips a `\r` in front of the `\n`, so "go\r" is not one of them.
+    let expected: Vec<Vec<u8>> = vec![b"\ngo".to_vec(), b"go".to_vec(), b"go\n".to_vec()];
+    assert_eq!(found, expected);
+}
+
+fn short_read_panics(short_reads: bool) -> Vec<String> {
+    let found = Rc::new(RefCell::new(Vec::new()));
+    let found1 = found.clone();
+    let consumer = move |complete| {
+        match complete {
+            ::seer::ExecutionComplete {
+                result: Err(::seer::StaticEvalError::Panic { message, .. }), ..
+            } => {
+                found1.borrow_mut().push(message.unwrap());
+                true
+            }
+            ::seer::ExecutionComplete { result: Ok(()), .. } => true,
+            ::seer::ExecutionComplete { result: Err(e), input, .. } => {
+                panic!("unexpected error {:?} with input {:?}", e, input)
+            }
+        }
+    };
+
+    let args = vec!["seer".to_string(), "tests/symbolic/short_read.rs".to_string()];
+    ::seer::ExecutionConfig::new()
+        .consumer(consumer)
+        .short_reads(short_reads)
+        .run(args);
+
+    let mut found = ::std::mem::replace(&mut *found.borrow_mut(), Vec::new());
+    found.sort();
+    found.dedup();
+    found
+}
+
+#[test]
+fn symbolic_short_reads() {
+    // Reads fill the buffer unless short reads are enabled.
+    assert!(short_read_panics(false).is_empty());
+    let expected = vec!["end of input", "failed", "interrupted", "short read"];
+    assert_eq!(short_read_panics(true), expected);
+}
@@ -0,0 +1,16 @@
+fn main() {
+    use std::io::Read;
+    let mut data = [0; 1];
+    let mut stdin = ::std::io::stdin();
+    stdin.read_exact(&mut data[..]).unwrap();
+
+    let mut table: [u32; 4] = [0x1111, 0x2222, 0x3333, 0x4444];
+
+    let idx = data[0] as usize; // 1
+    if idx < table.len() {
+        table[idx] = 0xdead_beef;
+        if table[1] == 0xdead_beef {
+            panic!()
+        }
+    }
+}
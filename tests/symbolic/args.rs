@@ -0,0 +1,6 @@
+fn main() {
+    match std::env::args().nth(1) {
+        Some(ref arg) if arg == "--magic" => panic!("magic argument"),
+        _ => {}
+    }
+}
@@ -0,0 +1,14 @@
+use std::io::Read;
+
+fn check(byte: u8) {
+    if byte == 42 {
+        panic!("found it");
+    }
+}
+
+fn main() {
+    let mut data = [0; 1];
+    let mut stdin = ::std::io::stdin();
+    stdin.read_exact(&mut data[..]).unwrap();
+    check(data[0]);
+}
@@ -0,0 +1,23 @@
+use std::io::Read;
+
+fn main() {
+    let mut data = [0; 2];
+    let mut stdin = ::std::io::stdin();
+    stdin.read_exact(&mut data[..]).unwrap();
+
+    // Four paths lead to each of the panics below.
+    let mut flags = 0;
+    if data[0] & 1 != 0 {
+        flags += 1;
+    }
+    if data[0] & 2 != 0 {
+        flags += 1;
+    }
+
+    if data[1] == 9 {
+        panic!("nine after {} flags", flags);
+    }
+    if data[1] == 10 {
+        panic!("ten after {} flags", flags);
+    }
+}
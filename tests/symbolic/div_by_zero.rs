@@ -0,0 +1,8 @@
+fn main() {
+    use std::io::Read;
+    let mut data = [0; 1];
+    let mut stdin = ::std::io::stdin();
+    stdin.read_exact(&mut data[..]).unwrap();
+
+    let _ = 100 / data[0];
+}
@@ -0,0 +1,18 @@
+fn main() {
+    use std::io::Read;
+    let mut data = [0; 2];
+    let mut stdin = ::std::io::stdin();
+    stdin.read_exact(&mut data[..]).unwrap();
+
+    if data[0] == 3 {
+        // seer cannot cast an input-dependent integer to a float.
+        let f = data[1] as f32;
+        if f > 1.0 {
+            return;
+        }
+    }
+
+    if data[0] == 4 && data[1] == 42 {
+        panic!()
+    }
+}
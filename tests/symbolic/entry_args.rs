@@ -0,0 +1,7 @@
+pub fn check(x: u32, data: &[u8], flag: &u8) {
+    if x == 0xdead && data[0] == 1 && data[1] == 7 && *flag == 3 {
+        panic!()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+pub fn sum(data: &[u8], extra: &u16) -> u32 {
+    let mut total = *extra as u32;
+    for &b in data {
+        total += b as u32;
+    }
+    total
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+fn main() {
+    match std::env::var("SEER_MODE") {
+        Ok(ref mode) if mode == "debug" => panic!("debug mode"),
+        Ok(_) => {}
+        Err(_) => panic!("SEER_MODE is not set"),
+    }
+}
@@ -0,0 +1,21 @@
+fn main() {
+    use std::io::Read;
+
+    let mut data = [0; 4];
+    let mut stdin = ::std::io::stdin();
+    stdin.read_exact(&mut data[..]).unwrap();
+
+    // Every iteration forks, but the paths join again right after the `if`.
+    let key = [3, 1, 4, 1];
+    let mut matches = 0;
+    for i in 0..4 {
+        if data[i] == key[i] {
+            matches += 1;
+        }
+    }
+
+    // should panic on [3, 1, 4, 1]
+    if matches == 4 {
+        panic!()
+    }
+}
@@ -0,0 +1,9 @@
+fn main() {
+    use std::io::Read;
+    let mut data = [0; 1];
+    let mut stdin = ::std::io::stdin();
+    stdin.read_exact(&mut data[..]).unwrap();
+
+    let b0 = data[0] as i8;
+    let _ = -b0; // overflows for data[0] = 128
+}
@@ -0,0 +1,14 @@
+fn main() {
+    use std::io::Read;
+    let mut data = [0; 4];
+    let mut stdin = ::std::io::stdin();
+    stdin.read_exact(&mut data[..]).unwrap();
+
+    let n = data[0] as u32 | (data[1] as u32) << 8 |
+        (data[2] as u32) << 16 | (data[3] as u32) << 24;
+
+    // The low byte of `n` is in range for a `u8`, but `n` is not.
+    if n & 0xff == 0 && n != 0 {
+        let _ = 1u8 << n;
+    }
+}
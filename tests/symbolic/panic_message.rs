@@ -0,0 +1,21 @@
+use std::io::Read;
+
+fn main() {
+    let mut data = [0; 2];
+    let mut stdin = ::std::io::stdin();
+    stdin.read_exact(&mut data[..]).unwrap();
+
+    match data[0] {
+        1 => panic!("invariant violated"),
+        2 => {
+            let x: Option<u8> = None;
+            x.unwrap();
+        }
+        3 => panic!("bad byte {}", data[1]),
+        4 => {
+            let x = 7;
+            panic!("got {}, expected {:?}", x, "seven")
+        }
+        _ => {}
+    }
+}
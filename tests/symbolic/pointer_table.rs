@@ -0,0 +1,17 @@
+fn main() {
+    use std::io::Read;
+    let mut data = [0; 1];
+    let mut stdin = ::std::io::stdin();
+    stdin.read_exact(&mut data[..]).unwrap();
+
+    let a = 10u32;
+    let b = 20u32;
+    let c = 30u32;
+    let d = 40u32;
+    let table: [&u32; 4] = [&a, &b, &c, &d];
+
+    let idx = data[0] as usize; // 2
+    if idx < table.len() && *table[idx] == 30 {
+        panic!()
+    }
+}
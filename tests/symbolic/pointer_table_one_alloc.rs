@@ -0,0 +1,14 @@
+fn main() {
+    use std::io::Read;
+    let mut data = [0; 1];
+    let mut stdin = ::std::io::stdin();
+    stdin.read_exact(&mut data[..]).unwrap();
+
+    let values = [10u32, 20, 30, 40];
+    let table: [&u32; 4] = [&values[1], &values[3], &values[2], &values[0]];
+
+    let idx = data[0] as usize; // 2
+    if idx < table.len() && *table[idx] == 30 {
+        panic!()
+    }
+}
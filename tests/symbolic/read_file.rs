@@ -0,0 +1,7 @@
+fn main() {
+    assert!(std::fs::File::open("missing.txt").is_err());
+    let contents = std::fs::read("config.txt").unwrap();
+    if contents.starts_with(b"debug") {
+        panic!("debug config");
+    }
+}
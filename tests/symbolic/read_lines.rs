@@ -0,0 +1,12 @@
+use std::io::BufRead;
+
+fn main() {
+    let stdin = std::io::stdin();
+    for line in stdin.lock().lines() {
+        match line {
+            Ok(ref line) if line == "go" => panic!("go"),
+            Ok(_) => {}
+            Err(_) => return,
+        }
+    }
+}
@@ -0,0 +1,12 @@
+use std::io::{ErrorKind, Read};
+
+fn main() {
+    let mut buf = [0; 4];
+    match std::io::stdin().read(&mut buf) {
+        Ok(0) => panic!("end of input"),
+        Ok(n) if n < buf.len() => panic!("short read"),
+        Ok(_) => {}
+        Err(ref e) if e.kind() == ErrorKind::Interrupted => panic!("interrupted"),
+        Err(_) => panic!("failed"),
+    }
+}
@@ -0,0 +1,14 @@
+fn main() {
+    use std::io::Read;
+    let mut data = [0; 1];
+    let mut stdin = ::std::io::stdin();
+    stdin.read_exact(&mut data[..]).unwrap();
+
+    let n = data[0] as usize;
+    if n < 8 {
+        let v: Vec<u8> = Vec::with_capacity(n);
+        if v.capacity() == 5 {
+            panic!()
+        }
+    }
+}
@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    #[test]
+    fn finds_magic_byte() {
+        let mut data = [0];
+        let mut stdin = ::std::io::stdin();
+        stdin.read_exact(&mut data[..]).unwrap();
+
+        if data[0] == 7 {
+            panic!()
+        }
+    }
+
+    #[test]
+    fn always_passes() {
+        assert_eq!(1 + 1, 2);
+    }
+
+    #[test]
+    #[ignore]
+    fn ignored() {
+        panic!()
+    }
+
+    #[test]
+    #[should_panic]
+    fn expected_to_panic() {
+        panic!()
+    }
+}